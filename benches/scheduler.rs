@@ -0,0 +1,65 @@
+//! Measures how simulation time scales with the number of actors.
+//!
+//! Set HISTRION_BENCH_MAX_ACTORS to change the largest population tried.
+
+use std::sync::Arc;
+use std::time::Instant as Clock;
+
+use histrion::Workspace;
+use histrion::action::*;
+use histrion::time::Interval;
+
+const WAITS_PER_ACTOR: usize = 4;
+
+fn populate(actors: usize) -> Arc<[Action]> {
+    let mut script = Vec::with_capacity(actors * 2);
+
+    for i in 0 .. actors {
+        let name: Arc<str> = format!("Actor{}", i).into();
+
+        let waits = (0 .. WAITS_PER_ACTOR).map(|step| {
+            let secs = 1 + (i * 7919 + step * 104_729) % 1000;
+            Action::Wait { interval: Interval::from_f64(secs as f64) }
+        }).collect::<Vec<_>>();
+
+        script.push(Action::Spawn { name: name.clone() });
+        script.push(Action::AsActor { name, script: waits.into() });
+    }
+
+    script.into()
+}
+
+fn main() {
+    let max_actors = std::env::var("HISTRION_BENCH_MAX_ACTORS").ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1_000_000);
+
+    let mut actors = 1000;
+
+    while actors <= max_actors {
+        let script = populate(actors);
+
+        let mut workspace = Workspace::new();
+        workspace.set_echo(false);
+
+        let start = Clock::now();
+        workspace.perform(script).unwrap();
+        let setup = start.elapsed();
+
+        let mut updates = 0u64;
+        while !workspace.has_halted() {
+            workspace.update().unwrap();
+            updates += 1;
+        }
+
+        let total = start.elapsed();
+        let per_update = (total - setup).as_nanos() as f64 / updates as f64;
+
+        println!(
+            "{:>9} actors: {:>9} updates in {:>8.3}s (setup {:.3}s), {:>8.1} ns/update",
+            actors, updates, total.as_secs_f64(), setup.as_secs_f64(), per_update,
+        );
+
+        actors *= 10;
+    }
+}
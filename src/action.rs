@@ -4,7 +4,10 @@ use std::sync::Arc;
 use ordered_float::NotNan;
 use vek::*;
 
-use crate::time::Interval;
+use crate::builtins::Builtin;
+use crate::random::Distribution;
+use crate::time::{Instant, Interval};
+use crate::travel::DriveProfile;
 
 #[derive(Clone, Debug)]
 pub enum Action {
@@ -22,6 +25,28 @@ pub enum Action {
         interval: Interval,
     },
 
+    /// Wait until the clock reads `at`, or not at all if it already has
+    WaitUntil {
+        at: Instant,
+    },
+
+    /// Perform `script` in a fresh fiber every `interval`, from `from` (or
+    /// now) up to and including `until`, or for as long as the actor lives.
+    /// Without `until`, the story only ends at a `halt`.
+    Every {
+        interval: Interval,
+        from: Option<Instant>,
+        until: Option<Instant>,
+        script: Arc<[Action]>,
+    },
+
+    /// Wait until `subject` comes within `distance` light-seconds of `target`
+    WaitNear {
+        subject: Arc<Expr>,
+        distance: Arc<Expr>,
+        target: Arc<Expr>,
+    },
+
     ListenFor {
         head: Arc<str>,
         args: Arc<[Expr]>,
@@ -36,6 +61,11 @@ pub enum Action {
         value: Vec3<f64>,
     },
 
+    TravelTo {
+        target: Arc<Expr>,
+        profile: DriveProfile,
+    },
+
     Transmit {
         head: Arc<str>,
         args: Arc<[Expr]>,
@@ -43,6 +73,18 @@ pub enum Action {
 
     Die,
 
+    /// Label the actor, so neighborhood queries can pick it out
+    Tag {
+        tag: Arc<str>,
+    },
+
+    /// Go aboard another actor, and follow its course until disembarking
+    Board {
+        container: Arc<Expr>,
+    },
+
+    Disembark,
+
     WriteLocal {
         name: Arc<str>,
         value: Arc<Expr>,
@@ -59,6 +101,98 @@ pub enum Action {
     },
 
     Return,
+
+    /// Skip the next `skip` actions unless the condition holds
+    If {
+        condition: Arc<Condition>,
+        skip: usize,
+    },
+
+    /// Skip the next `skip` actions, jumping over an `else` block
+    Skip {
+        skip: usize,
+    },
+
+    /// Another action, with a writer's notes attached
+    Annotated {
+        action: Arc<Action>,
+        annotation: Arc<Annotation>,
+    },
+}
+
+/// Prose, tags and a chapter attached to an action with `-- ...`, and
+/// carried onto the events it records
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Annotation {
+    pub text: Option<Arc<str>>,
+    pub tags: Vec<Arc<str>>,
+    pub chapter: Option<Arc<str>>,
+}
+
+/// Something a script can test with `if`
+#[derive(Clone, Debug)]
+pub enum Condition {
+    /// Whether the actor has heard or sent this signal
+    Knows {
+        head: Arc<str>,
+        args: Arc<[Expr]>,
+    },
+
+    Not {
+        condition: Arc<Condition>,
+    },
+}
+
+impl Action {
+    /// A short name for the kind of action, which `Breakpoint::Action`
+    /// matches. It is the saga keyword where the action has one; setting
+    /// `self.accel` is "accel", assigning a local is "assign", and the jump
+    /// over an `else` block is "else".
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Action::Halt => "halt",
+            Action::Trace { .. } => "trace",
+            Action::Spawn { .. } => "spawn",
+            Action::Wait { .. } => "wait",
+            Action::WaitNear { .. } => "wait",
+            Action::WaitUntil { .. } => "wait",
+            Action::Every { .. } => "every",
+            Action::ListenFor { .. } => "listen",
+            Action::AsActor { .. } => "as",
+            Action::SetAccel { .. } => "accel",
+            Action::TravelTo { .. } => "travel",
+            Action::Transmit { .. } => "transmit",
+            Action::Die => "die",
+            Action::Tag { .. } => "tag",
+            Action::Board { .. } => "board",
+            Action::Disembark => "disembark",
+            Action::WriteLocal { .. } => "assign",
+            Action::DefGlobalMethod { .. } => "def",
+            Action::Call { .. } => "call",
+            Action::Return => "return",
+            Action::If { .. } => "if",
+            Action::Skip { .. } => "else",
+            Action::Annotated { action, .. } => action.keyword(),
+        }
+    }
+
+    /// The action itself, without any annotation
+    pub fn unannotated(&self) -> &Action {
+        match self {
+            Action::Annotated { action, .. } => action.unannotated(),
+            other => other,
+        }
+    }
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.tags.is_empty() && self.chapter.is_none()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|other| other.as_ref() == tag)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -77,6 +211,30 @@ pub enum Expr {
     Var {
         name: Arc<str>,
     },
+
+    Builtin {
+        func: Builtin,
+        args: Arc<[Expr]>,
+    },
+
+    Random {
+        dist: Distribution,
+        args: Arc<[Expr]>,
+    },
+
+    /// Actors within `radius` of `center`, nearest first
+    Within {
+        center: Arc<Expr>,
+        radius: Arc<Expr>,
+        tag: Option<Arc<str>>,
+    },
+
+    /// The `count` actors nearest to `center`, nearest first
+    Nearest {
+        center: Arc<Expr>,
+        count: Arc<Expr>,
+        tag: Option<Arc<str>>,
+    },
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -90,6 +248,7 @@ pub enum Value {
     ActorId(specs::Entity),
     Num(NotNan<f64>),
     Struct(BTreeMap<Arc<str>, Value>),
+    List(Arc<[Value]>),
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use specs::prelude::*;
+
+use crate::{Error, Workspace};
+use crate::action::Action;
+use crate::task::Fiber;
+use crate::time::Instant;
+
+/// Where a fiber was when one of its actions failed
+#[derive(Clone, Debug)]
+pub struct Backtrace {
+    pub time: Instant,
+    pub actor: Entity,
+    pub actor_name: Option<Arc<str>>,
+    pub fiber: u64,
+
+    /// The failing frame first, then each caller in turn
+    pub frames: Vec<BacktraceFrame>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BacktraceFrame {
+    /// The method this frame was running, or None for a top-level script
+    pub method: Option<Arc<str>>,
+
+    /// Index of the action being performed: the failing one in the innermost
+    /// frame, and the call in the frames around it
+    pub pc: usize,
+
+    pub action: Option<Action>,
+}
+
+impl Error {
+    /// The error itself, without any backtrace wrapped around it
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::InFiber { error, .. } => error.root(),
+            other => other,
+        }
+    }
+
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self {
+            Error::InFiber { backtrace, .. } => Some(backtrace),
+            _ => None,
+        }
+    }
+}
+
+impl Workspace {
+    /// Wrap an error from the action the fiber has just fetched
+    pub(crate) fn fail_at(&self, fiber: &Fiber, error: Error) -> Error {
+        // Every frame has moved past the action it is performing: the failing
+        // one in the innermost frame, and a call in each of the others
+        let frames = fiber.stack.iter().rev().map(|frame| {
+            let pc = frame.pc.saturating_sub(1);
+
+            BacktraceFrame {
+                method: frame.method.clone(),
+                pc,
+                action: frame.script.get(pc).cloned(),
+            }
+        }).collect();
+
+        self.fail_in(fiber.me, fiber.id, frames, error)
+    }
+
+    /// Wrap an error from the fiber, when its stack is no longer to hand
+    pub(crate) fn fail_in(&self, actor: Entity, fiber: u64, frames: Vec<BacktraceFrame>, error: Error) -> Error {
+        if let Error::InFiber { .. } = error {
+            return error;
+        }
+
+        Error::InFiber {
+            error: Box::new(error),
+            backtrace: Box::new(Backtrace {
+                time: self.now,
+                actor,
+                actor_name: self.name_of(actor),
+                fiber,
+                frames,
+            }),
+        }
+    }
+}
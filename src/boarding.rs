@@ -0,0 +1,107 @@
+use specs::prelude::*;
+use specs::{Component, VecStorage};
+use vek::Vec3;
+
+use crate::{Error, Position, Result, Trajectory, Workspace};
+
+/// The actor this one is aboard. While aboard, an actor has no course of its
+/// own and goes wherever its container goes.
+#[derive(Copy, Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Parent(pub Entity);
+
+/// How close, in light-seconds, an actor must be to board another
+pub const BOARDING_RANGE: f64 = 1e-3;
+
+impl Workspace {
+    /// What the actor is aboard, if anything
+    pub fn parent_of(&self, id: Entity) -> Option<Entity> {
+        self.world.read_component::<Parent>().get(id).map(|parent| parent.0)
+    }
+
+    /// Everyone directly aboard the actor
+    pub fn passengers_of(&self, id: Entity) -> Vec<Entity> {
+        let entities = self.world.entities();
+        let parents = self.world.read_component::<Parent>();
+
+        (&entities, &parents).join()
+            .filter(|&(_, parent)| parent.0 == id)
+            .map(|(passenger, _)| passenger)
+            .collect()
+    }
+
+    /// Go aboard `container`, taking up its course from now on
+    pub(crate) fn board(&mut self, me: Entity, container: Entity) -> Result<()> {
+        let mut outer = Some(container);
+        while let Some(id) = outer {
+            if id == me {
+                return Err(Error::CannotBoard {
+                    name: self.name_of(me).unwrap_or_default(),
+                    container: self.name_of(container).unwrap_or_default(),
+                });
+            }
+            outer = self.parent_of(id);
+        }
+
+        let distance = self.get_position(me)?.0.distance(self.get_position(container)?.0);
+        if distance > BOARDING_RANGE {
+            return Err(Error::OutOfReach {
+                name: self.name_of(me).unwrap_or_default(),
+                container: self.name_of(container).unwrap_or_default(),
+                distance,
+            });
+        }
+
+        self.world.write_component::<Parent>().insert(me, Parent(container))
+            .map_err(|_err| Error::CouldNotWrite { component: "Parent" })?;
+
+        let course = self.world.read_component::<Trajectory>()
+            .get(container).cloned().unwrap_or_default();
+
+        // Boarding moves the actor to its container, so forget where it was
+        self.world.write_component::<Position>().remove(me);
+        self.set_trajectory(me, course)
+    }
+
+    /// Leave whatever the actor is aboard, drifting on at its current velocity
+    pub(crate) fn disembark(&mut self, me: Entity) -> Result<()> {
+        if self.world.write_component::<Parent>().remove(me).is_none() {
+            return Ok(());
+        }
+
+        let place = self.get_position(me)?;
+        let velocity = self.world.read_component::<Trajectory>()
+            .get(me).cloned().unwrap_or_default()
+            .velocity_at(self.now);
+
+        let course = if velocity.magnitude_squared() == 0.0 {
+            Trajectory::Fixed { value: place }
+        } else {
+            Trajectory::Linear {
+                start_place: place,
+                start_time: self.now,
+                start_velocity: velocity,
+                accel: Vec3::zero(),
+            }
+        };
+
+        self.set_trajectory(me, course)
+    }
+
+    /// Put everyone aboard the actor off where they are, as it dies
+    pub(crate) fn set_down_passengers(&mut self, container: Entity) -> Result<()> {
+        for passenger in self.passengers_of(container) {
+            self.disembark(passenger)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fail unless the actor is free to set its own course
+    pub(crate) fn check_not_aboard(&self, me: Entity) -> Result<()> {
+        match self.parent_of(me) {
+            Some(_) => Err(Error::Aboard { name: self.name_of(me).unwrap_or_default() }),
+            None => Ok(()),
+        }
+    }
+}
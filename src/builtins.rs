@@ -0,0 +1,147 @@
+use specs::prelude::*;
+use vek::Vec3;
+
+use crate::{Error, Position, Result, Trajectory, Workspace};
+use crate::action::Value;
+use crate::travel::DriveProfile;
+
+/// Functions that scripts can call from any expression
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Builtin {
+    /// Distance between two places, in light-seconds
+    Distance,
+
+    /// Time for light to cross between two places, in seconds
+    LightDelay,
+
+    /// Unit vector pointing from the first place toward the second
+    Bearing,
+
+    /// Velocity of the second place as seen from the first
+    RelativeVelocity,
+
+    /// Thrust-brake travel time between two places at the given accel
+    TravelTime,
+}
+
+/// Place and velocity of an actor, or of a bare position struct
+struct Kinematics {
+    place: Vec3<f64>,
+    velocity: Vec3<f64>,
+}
+
+impl Builtin {
+    pub const ALL: &'static [Builtin] = &[
+        Builtin::Distance,
+        Builtin::LightDelay,
+        Builtin::Bearing,
+        Builtin::RelativeVelocity,
+        Builtin::TravelTime,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Distance => "distance",
+            Builtin::LightDelay => "light_delay",
+            Builtin::Bearing => "bearing",
+            Builtin::RelativeVelocity => "relative_velocity",
+            Builtin::TravelTime => "travel_time",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Builtin::ALL.iter().cloned().find(|builtin| builtin.name() == name)
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Builtin::TravelTime => 3,
+            _ => 2,
+        }
+    }
+}
+
+impl Workspace {
+    pub(crate) fn eval_builtin(&mut self, func: Builtin, args: &[Value]) -> Result<Value> {
+        if args.len() != func.arity() {
+            return Err(Error::ArgListMismatch {
+                name: func.name().into(),
+                wanted: func.arity(),
+                got: args.len(),
+            });
+        }
+
+        let from = self.kinematics(func, &args[0])?;
+        let to = self.kinematics(func, &args[1])?;
+        let delta = to.place - from.place;
+
+        Ok(match func {
+            Builtin::Distance | Builtin::LightDelay => {
+                // Positions are in light-seconds, so the two coincide
+                num(delta.magnitude())
+            },
+
+            Builtin::Bearing => {
+                let distance = delta.magnitude();
+                if distance > 0.0 {
+                    vector(delta / distance)
+                } else {
+                    vector(Vec3::zero())
+                }
+            },
+
+            Builtin::RelativeVelocity => vector(to.velocity - from.velocity),
+
+            Builtin::TravelTime => {
+                let accel = match &args[2] {
+                    &Value::Num(accel) if accel.into_inner() > 0.0 => accel.into_inner(),
+                    other => return Err(Error::BadArgument {
+                        name: func.name().into(),
+                        value: other.clone(),
+                    }),
+                };
+
+                let profile = DriveProfile::ThrustBrake { accel };
+                num(f64::from(profile.travel_time(delta.magnitude())))
+            },
+        })
+    }
+
+    fn kinematics(&self, func: Builtin, value: &Value) -> Result<Kinematics> {
+        match value {
+            &Value::ActorId(id) => {
+                let place = self.get_position(id)?.0;
+                let velocity = self.world.read_component::<Trajectory>().get(id)
+                    .map(|trajectory| trajectory.velocity_at(self.now))
+                    .unwrap_or_default();
+                Ok(Kinematics { place, velocity })
+            },
+
+            Value::Struct(dict) => {
+                let axis = |name: &str| match dict.get(name) {
+                    Some(&Value::Num(n)) => Ok(n.into_inner()),
+                    _ => Err(Error::BadArgument {
+                        name: func.name().into(),
+                        value: value.clone(),
+                    }),
+                };
+
+                let place = Vec3::new(axis("x")?, axis("y")?, axis("z")?);
+                Ok(Kinematics { place, velocity: Vec3::zero() })
+            },
+
+            other => Err(Error::BadArgument {
+                name: func.name().into(),
+                value: other.clone(),
+            }),
+        }
+    }
+}
+
+fn num(value: f64) -> Value {
+    Value::Num(value.into())
+}
+
+fn vector(v: Vec3<f64>) -> Value {
+    Position(v).into()
+}
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use specs::Entity;
+
+use crate::Workspace;
+use crate::history::{Event, EventId, EventKind, History};
+use crate::report;
+use crate::time::{Instant, Interval};
+
+/// Why one event led to another
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Cause {
+    /// The same fiber carried on to its next action
+    NextStep,
+
+    /// A transmission woke up a listening fiber
+    Signal,
+
+    /// A `spawn` created the actor that acted next
+    Spawn,
+
+    /// An `as ... do` started a fiber on another actor
+    Delegate,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CausalEdge {
+    pub from: EventId,
+    pub to: EventId,
+    pub cause: Cause,
+}
+
+/// The causal links between events, kept up to date as each one is recorded
+/// so queries don't have to go over the whole history again
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CausalIndex {
+    edges: Vec<CausalEdge>,
+    successors: Vec<Vec<EventId>>,
+    last_in_fiber: HashMap<u64, EventId>,
+    spawned_by: HashMap<Entity, EventId>,
+}
+
+impl CausalIndex {
+    /// Link in the event about to be recorded as `id`, given the event that
+    /// started its fiber, if any
+    pub(crate) fn add(&mut self, id: EventId, event: &Event, origin: Option<EventId>) {
+        self.successors.push(Vec::new());
+
+        match self.last_in_fiber.insert(event.fiber, id) {
+            Some(prev) => self.link(prev, id, Cause::NextStep),
+
+            None => {
+                if let Some(origin) = origin {
+                    self.link(origin, id, Cause::Delegate);
+                }
+
+                if let Some(spawn) = self.spawned_by.remove(&event.actor) {
+                    self.link(spawn, id, Cause::Spawn);
+                }
+            },
+        }
+
+        match event.kind {
+            EventKind::Spawned { child, .. } => {
+                self.spawned_by.insert(child, id);
+            },
+
+            EventKind::Received { transmission, .. } => {
+                self.link(transmission, id, Cause::Signal);
+            },
+
+            _ => (),
+        }
+    }
+
+    fn link(&mut self, from: EventId, to: EventId, cause: Cause) {
+        self.edges.push(CausalEdge { from, to, cause });
+        self.successors[from].push(to);
+    }
+}
+
+impl History {
+    /// Every direct causal link between recorded events
+    pub fn causal_edges(&self) -> &[CausalEdge] {
+        &self.causality().edges
+    }
+
+    /// Events the given one led to directly
+    pub fn causal_successors(&self, id: EventId) -> &[EventId] {
+        self.causality().successors.get(id).map_or(&[], |successors| successors.as_slice())
+    }
+}
+
+/// Write the causal structure of the history as a Graphviz digraph, with
+/// one cluster of events per actor
+pub fn write_dot(workspace: &Workspace, out: &mut dyn Write) -> io::Result<()> {
+    let history = workspace.history();
+
+    let mut by_actor: BTreeMap<String, Vec<EventId>> = BTreeMap::new();
+    for (id, event) in history.events().iter().enumerate() {
+        by_actor.entry(report::actor_text(workspace, event.actor)).or_default().push(id);
+    }
+
+    writeln!(out, "digraph causality {{")?;
+    writeln!(out, "    rankdir=LR;")?;
+    writeln!(out, "    node [shape=box, fontname=\"sans-serif\"];")?;
+
+    for (cluster, (actor, ids)) in by_actor.iter().enumerate() {
+        writeln!(out, "    subgraph cluster_{} {{", cluster)?;
+        writeln!(out, "        label={};", dot_string(actor))?;
+
+        for &id in ids {
+            let event = &history.events()[id];
+            let label = format!("{}\n{}", report::duration_text(f64::from(event.time)), report::event_text(workspace, &event.kind));
+            writeln!(out, "        e{} [label={}];", id, dot_string(&label))?;
+        }
+
+        writeln!(out, "    }}")?;
+    }
+
+    for edge in history.causal_edges() {
+        let style = match edge.cause {
+            Cause::NextStep => "",
+            Cause::Signal => " [style=dashed, color=darkorange, label=\"signal\"]",
+            Cause::Spawn => " [style=bold, color=steelblue, label=\"spawned\"]",
+            Cause::Delegate => " [style=dotted, label=\"as\"]",
+        };
+
+        writeln!(out, "    e{} -> e{}{};", edge.from, edge.to, style)?;
+    }
+
+    writeln!(out, "}}")
+}
+
+fn dot_string(src: &str) -> String {
+    let mut dot = String::with_capacity(src.len() + 2);
+    dot.push('"');
+
+    for c in src.chars() {
+        match c {
+            '"' => dot.push_str("\\\""),
+            '\\' => dot.push_str("\\\\"),
+            '\n' => dot.push_str("\\n"),
+            c => dot.push(c),
+        }
+    }
+
+    dot.push('"');
+    dot
+}
+
+impl Workspace {
+    /// Whether a chain of recorded causes leads from `a` to `b`
+    pub fn caused(&self, a: EventId, b: EventId) -> bool {
+        a == b || self.consequences(a).get(b).cloned().unwrap_or(false)
+    }
+
+    /// Whether `a` could have influenced `b`, either because it actually did,
+    /// or because light leaving `a` would have reached `b` in time
+    pub fn could_influence(&self, a: EventId, b: EventId) -> bool {
+        let history = self.history();
+
+        let (first, second) = match (history.get(a), history.get(b)) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return false,
+        };
+
+        if self.caused(a, b) {
+            return true;
+        }
+
+        let elapsed = f64::from(first.time.delta(second.time));
+        let here = history.place_of(first.actor, first.time).0;
+        let there = history.place_of(second.actor, second.time).0;
+
+        elapsed >= 0.0 && elapsed >= (there - here).magnitude()
+    }
+
+    /// The first event of `actor` that `event` actually led to, if any.
+    /// Signals still arrive the instant they are sent, so this can come
+    /// before `earliest_knowledge`, which waits for the light.
+    pub fn first_informed(&self, actor: Entity, event: EventId) -> Option<EventId> {
+        let reached = self.consequences(event);
+
+        self.history().events().iter().enumerate()
+            .skip(event + 1)
+            .find(|&(id, other)| other.actor == actor && reached[id])
+            .map(|(id, _)| id)
+    }
+
+    /// The earliest time that light leaving `event` could reach `actor`,
+    /// following the actor's recorded trajectory. None if the actor outruns
+    /// the light, which only a faster-than-light trajectory can do. This is
+    /// a bound from physics, not from the history; see `first_informed`.
+    pub fn earliest_knowledge(&self, actor: Entity, event: EventId) -> Option<Instant> {
+        const MAX_ITERATIONS: usize = 1000;
+        const TOLERANCE: f64 = 1e-6;
+
+        let history = self.history();
+        let origin = history.get(event)?;
+        let source = history.place_of(origin.actor, origin.time).0;
+
+        let created = self.creation_date_of(actor).unwrap_or_default();
+        let mut time = origin.time.max(created);
+
+        // Chase the actor with the light front until they meet
+        for _ in 0 .. MAX_ITERATIONS {
+            let distance = (history.place_of(actor, time).0 - source).magnitude();
+            let arrival = origin.time + Interval::from_f64(distance);
+
+            if f64::from(time.delta(arrival)) <= TOLERANCE {
+                return Some(time);
+            }
+
+            time = arrival;
+        }
+
+        None
+    }
+
+    /// Which events the given one led to, directly or indirectly
+    fn consequences(&self, from: EventId) -> Vec<bool> {
+        let history = self.history();
+        let mut reached = vec![false; history.len()];
+
+        let mut frontier = vec![from];
+        while let Some(id) = frontier.pop() {
+            for &next in history.causal_successors(id) {
+                if !reached[next] {
+                    reached[next] = true;
+                    frontier.push(next);
+                }
+            }
+        }
+
+        reached
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use specs::Entity;
+
+use crate::Workspace;
+use crate::action::Action;
+use crate::history::{Event, EventId, EventKind};
+use crate::pretty_print::fmt_actor_name;
+use crate::report::{actor_text, duration_text, event_text, signal_text};
+use crate::script::TimeUnit;
+use crate::time::Instant;
+
+/// A calendar day of the story, counting from year 1, day 1 at time zero
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Date {
+    year: i64,
+    day: i64,
+}
+
+impl Date {
+    fn of(time: Instant) -> Self {
+        Date::with_time_of_day(time).0
+    }
+
+    /// The date, and the seconds since that day began. Days are counted
+    /// whole from time zero, and each year starts on the first whole day of
+    /// its share of 365.2425, so some years have 366 days.
+    fn with_time_of_day(time: Instant) -> (Self, f64) {
+        let secs = f64::from(time);
+        let day_secs = f64::from(TimeUnit::Day);
+        let days_per_year = f64::from(TimeUnit::Year) / day_secs;
+
+        let days = (secs / day_secs).floor();
+        let year_start = |year: f64| (year * days_per_year).floor();
+
+        let mut year = (days / days_per_year).floor();
+        if year_start(year + 1.0) <= days {
+            year += 1.0;
+        }
+
+        let date = Date { year: year as i64 + 1, day: (days - year_start(year)) as i64 + 1 };
+        (date, secs - days * day_secs)
+    }
+}
+
+/// Write the history as a Markdown chronicle, one section per day with the
+/// deeds of each actor in turn. Only events that move the story along are
+/// told, along with any action a writer has annotated with prose.
+pub fn write_markdown(workspace: &Workspace, out: &mut dyn Write) -> io::Result<()> {
+    let events = workspace.history().events();
+
+    let mut receptions: HashMap<EventId, Vec<&Event>> = HashMap::new();
+    for event in events {
+        if let EventKind::Received { transmission, .. } = event.kind {
+            receptions.entry(transmission).or_default().push(event);
+        }
+    }
+
+    writeln!(out, "# Chronicle")?;
+
+    let mut start = 0;
+    while start < events.len() {
+        let date = Date::of(events[start].time);
+        let end = events[start ..].iter()
+            .position(|event| Date::of(event.time) != date)
+            .map_or(events.len(), |len| start + len);
+
+        let mut actors: Vec<(Entity, Vec<String>)> = Vec::new();
+
+        for (id, event) in events.iter().enumerate().take(end).skip(start) {
+            let line = match entry(workspace, id, event, &receptions) {
+                Some(line) => line,
+                None => continue,
+            };
+
+            match actors.iter_mut().find(|(actor, _)| *actor == event.actor) {
+                Some((_, lines)) => lines.push(line),
+                None => actors.push((event.actor, vec![line])),
+            }
+        }
+
+        if !actors.is_empty() {
+            writeln!(out, "\n## Year {}, day {}", date.year, date.day)?;
+
+            for (actor, lines) in actors {
+                writeln!(out, "\n### {}\n", actor_text(workspace, actor))?;
+
+                for line in lines {
+                    writeln!(out, "- {}", line)?;
+                }
+            }
+        }
+
+        start = end;
+    }
+
+    Ok(())
+}
+
+/// One line of the chronicle, or None if the event isn't worth telling
+fn entry(workspace: &Workspace, id: EventId, event: &Event, receptions: &HashMap<EventId, Vec<&Event>>) -> Option<String> {
+    let prose = event.annotation.as_ref().and_then(|annotation| annotation.text.clone());
+
+    let deed = match &event.kind {
+        EventKind::Spawned { name, .. } => format!("spawned {}", fmt_actor_name(name)),
+
+        EventKind::Transmitted { signal } => {
+            let mut deed = format!("transmitted {}", signal_text(workspace, signal));
+
+            for reception in receptions.get(&id).into_iter().flatten() {
+                deed.push_str(&format!(
+                    "; {} received it {} later",
+                    actor_text(workspace, reception.actor),
+                    duration_text(f64::from(reception.time) - f64::from(event.time)),
+                ));
+            }
+
+            deed
+        },
+
+        EventKind::Received { signal, transmission } => {
+            let sent = &workspace.history().events()[*transmission];
+            format!(
+                "received {} from {}, sent {} earlier",
+                signal_text(workspace, signal),
+                actor_text(workspace, sent.actor),
+                duration_text(f64::from(event.time) - f64::from(sent.time)),
+            )
+        },
+
+        EventKind::Performed { action: Action::Die } => "died".into(),
+
+        EventKind::Halted => "brought the story to an end".into(),
+
+        EventKind::Failed { error } => format!("failed: {}", error.root()),
+
+        EventKind::Killed { .. } => "was killed for failing".into(),
+
+        other if prose.is_some() => event_text(workspace, other),
+
+        _ => return None,
+    };
+
+    let line = format!("{} {}", time_of_day(event.time), deed);
+
+    Some(match prose {
+        Some(prose) => format!("{}: *{}*", line, prose),
+        None => line,
+    })
+}
+
+/// Hours, minutes and seconds since the start of the day
+fn time_of_day(time: Instant) -> String {
+    let secs = Date::with_time_of_day(time).1 as u64;
+
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
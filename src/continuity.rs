@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use specs::Entity;
+
+use crate::{Trajectory, Workspace};
+use crate::history::{EventId, EventKind};
+use crate::report;
+use crate::time::Instant;
+
+/// Something in the recorded history that could not have happened
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inconsistency {
+    /// An actor did something, or heard something, after it died
+    ActedWhileDead {
+        event: EventId,
+        death: EventId,
+    },
+
+    /// An actor did something before it was created
+    ActedBeforeCreation {
+        event: EventId,
+        created: Instant,
+    },
+
+    /// A signal arrived sooner than light could have carried it
+    FasterThanLight {
+        transmission: EventId,
+        reception: EventId,
+        light_delay: f64,
+    },
+
+    /// An actor's trajectory went faster than light
+    Superluminal {
+        actor: Entity,
+        since: Instant,
+        speed: f64,
+    },
+}
+
+impl Workspace {
+    /// Look through the recorded history for events that break the rules of
+    /// the setting, in the order they happened
+    pub fn check_continuity(&self) -> Vec<Inconsistency> {
+        const TOLERANCE: f64 = 1e-9;
+
+        let history = self.history();
+        let mut problems = Vec::new();
+        let mut deaths: HashMap<Entity, EventId> = HashMap::new();
+
+        for (id, event) in history.events().iter().enumerate() {
+            if let Some(&death) = deaths.get(&event.actor) {
+                problems.push(Inconsistency::ActedWhileDead { event: id, death });
+            }
+
+            if let Some(created) = self.creation_date_of(event.actor) {
+                if event.time < created {
+                    problems.push(Inconsistency::ActedBeforeCreation { event: id, created });
+                }
+            }
+
+            match event.kind {
+                ref kind if kind.is_death() => {
+                    deaths.entry(event.actor).or_insert(id);
+                },
+
+                EventKind::Received { transmission, .. } => {
+                    let sent = &history.events()[transmission];
+                    let here = history.place_of(sent.actor, sent.time).0;
+                    let there = history.place_of(event.actor, event.time).0;
+
+                    let light_delay = (there - here).magnitude();
+                    let delay = f64::from(sent.time.delta(event.time));
+
+                    if delay + TOLERANCE < light_delay {
+                        problems.push(Inconsistency::FasterThanLight {
+                            transmission,
+                            reception: id,
+                            light_delay,
+                        });
+                    }
+                },
+
+                _ => (),
+            }
+        }
+
+        for (_, actor) in self.globals() {
+            let segments = history.segments_of(actor);
+
+            for (index, segment) in segments.iter().enumerate() {
+                let until = segments.get(index + 1)
+                    .map_or(self.now(), |next| next.since)
+                    .max(segment.since);
+
+                let speed = top_speed(&segment.trajectory, segment.since, until);
+
+                if speed > 1.0 + TOLERANCE {
+                    problems.push(Inconsistency::Superluminal {
+                        actor,
+                        since: segment.since,
+                        speed,
+                    });
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Fastest speed along a trajectory between two instants, in units of c
+fn top_speed(trajectory: &Trajectory, from: Instant, until: Instant) -> f64 {
+    match *trajectory {
+        Trajectory::Fixed { .. } => 0.0,
+
+        // Speed under constant accel is convex in time, so peaks at an end
+        Trajectory::Linear { .. } => {
+            trajectory.velocity_at(from).magnitude()
+                .max(trajectory.velocity_at(until).magnitude())
+        },
+
+        Trajectory::Transfer { start_place, end_place, profile, .. } => {
+            profile.peak_speed((end_place.0 - start_place.0).magnitude())
+        },
+    }
+}
+
+/// Explain an inconsistency in words, naming the actors involved
+pub fn describe(workspace: &Workspace, problem: &Inconsistency) -> String {
+    let history = workspace.history();
+
+    let event_text = |id: EventId| {
+        let event = &history.events()[id];
+        format!(
+            "{} did `{}` at {}",
+            report::actor_text(workspace, event.actor),
+            report::event_text(workspace, &event.kind),
+            report::duration_text(f64::from(event.time)),
+        )
+    };
+
+    match *problem {
+        Inconsistency::ActedWhileDead { event, death } => {
+            let died = history.events()[death].time;
+            format!("{}, after dying at {}", event_text(event), report::duration_text(f64::from(died)))
+        },
+
+        Inconsistency::ActedBeforeCreation { event, created } => {
+            format!("{}, before being created at {}", event_text(event), report::duration_text(f64::from(created)))
+        },
+
+        Inconsistency::FasterThanLight { transmission, reception, light_delay } => {
+            format!(
+                "{}, but light needs {} to get there from where {}",
+                event_text(reception),
+                report::duration_text(light_delay),
+                event_text(transmission),
+            )
+        },
+
+        Inconsistency::Superluminal { actor, since, speed } => {
+            format!(
+                "{} travels at {:.3}c from {}",
+                report::actor_text(workspace, actor),
+                speed,
+                report::duration_text(f64::from(since)),
+            )
+        },
+    }
+}
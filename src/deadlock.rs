@@ -0,0 +1,115 @@
+use specs::prelude::*;
+
+use crate::{Agenda, Workspace};
+use crate::action::{Action, Signal};
+use crate::history::EventKind;
+use crate::proximity::StalledWatch;
+use crate::report;
+use crate::time::Instant;
+
+/// A fiber still waiting on a signal when there was nothing left to run
+#[derive(Clone, Debug)]
+pub struct LostListener {
+    pub actor: Entity,
+    pub fiber: u64,
+    pub signal: Signal,
+
+    /// When it started listening, if that made it into the history
+    pub since: Option<Instant>,
+
+    /// Whether any known script transmits a signal with the same head and
+    /// number of arguments. Arguments are only known at run time, so this
+    /// says a matching transmit could exist, not that it would match.
+    pub has_transmitter: bool,
+}
+
+impl Workspace {
+    /// Whether the simulation halted because it ran out of queued tasks,
+    /// rather than by performing `halt`
+    pub fn ran_dry(&self) -> bool {
+        self.ran_dry
+    }
+
+    /// Every fiber parked waiting for a signal, ordered by actor and head
+    pub fn lost_listeners(&self) -> Vec<LostListener> {
+        let entities = self.world.entities();
+        let agendas = self.world.read_component::<Agenda>();
+
+        let mut lost = Vec::new();
+
+        for (actor, agenda) in (&entities, &agendas).join() {
+            for (signal, waiting) in agenda.listening.iter() {
+                lost.push(LostListener {
+                    actor,
+                    fiber: waiting.fiber.id,
+                    signal: signal.clone(),
+                    since: self.listening_since(waiting.fiber.id, &signal.head),
+                    has_transmitter: self.transmits(&signal.head, signal.body.len()),
+                });
+            }
+        }
+
+        lost.sort_by(|a, b| (a.actor, &a.signal.head).cmp(&(b.actor, &b.signal.head)));
+        lost
+    }
+
+    fn listening_since(&self, fiber: u64, head: &str) -> Option<Instant> {
+        self.history().events().iter().rev()
+            .find(|event| event.fiber == fiber && match &event.kind {
+                EventKind::Performed { action: Action::ListenFor { head: other, .. } } => other.as_ref() == head,
+                _ => false,
+            })
+            .map(|event| event.time)
+    }
+
+    fn transmits(&self, head: &str, arity: usize) -> bool {
+        let methods = self.methods.values().map(|method| &method.script);
+
+        self.scripts.iter().chain(methods)
+            .any(|script| script_transmits(script, head, arity))
+    }
+}
+
+fn script_transmits(script: &[Action], head: &str, arity: usize) -> bool {
+    script.iter().any(|action| match action {
+        Action::Transmit { head: other, args } => other.as_ref() == head && args.len() == arity,
+        Action::AsActor { script, .. } => script_transmits(script, head, arity),
+        Action::Every { script, .. } => script_transmits(script, head, arity),
+        Action::DefGlobalMethod { body, .. } => script_transmits(&body.script, head, arity),
+        Action::Annotated { action, .. } => script_transmits(std::slice::from_ref(action.as_ref()), head, arity),
+        _ => false,
+    })
+}
+
+/// Explain why a watch set aside will never wake, in words
+pub fn describe_watch(workspace: &Workspace, watch: &StalledWatch) -> String {
+    format!(
+        "{} is still waiting for {} to come within {} of {}; on their present courses they never do",
+        report::actor_text(workspace, watch.actor),
+        report::actor_text(workspace, watch.subject),
+        watch.distance,
+        report::actor_text(workspace, watch.target),
+    )
+}
+
+/// Explain why a listener will never wake, in words
+pub fn describe(workspace: &Workspace, listener: &LostListener) -> String {
+    let since = match listener.since {
+        Some(time) => format!(" since {}", report::duration_text(f64::from(time))),
+        None => String::new(),
+    };
+
+    let verdict = if listener.has_transmitter {
+        "a matching transmit exists, but did not reach it"
+    } else {
+        "nothing in the scripts transmits it"
+    };
+
+    format!(
+        "{} is still listening for {}{}; {}",
+        report::actor_text(workspace, listener.actor),
+        report::signal_text(workspace, &listener.signal),
+        since,
+        verdict,
+    )
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use specs::prelude::*;
+
+use crate::{Result, Workspace};
+use crate::action::{Action, Expr, Value};
+use crate::task::Fiber;
+use crate::time::Instant;
+
+/// A condition under which the debugger pauses, checked before each action
+#[derive(Clone, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// Any action of this kind, as named by `Action::keyword`
+    Action {
+        keyword: Arc<str>,
+    },
+
+    /// Any action performed by this actor
+    Actor {
+        id: Entity,
+    },
+
+    /// Transmitting, listening for or receiving signals with this head
+    Signal {
+        head: Arc<str>,
+    },
+
+    /// The first action at or after this instant; fires only once
+    Instant {
+        at: Instant,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PauseReason {
+    Breakpoint(Breakpoint),
+    Step,
+}
+
+/// Where execution stopped, and what the watch expressions said about it
+#[derive(Clone, Debug)]
+pub struct Pause {
+    pub reason: PauseReason,
+    pub time: Instant,
+    pub actor: Entity,
+    pub fiber: u64,
+
+    /// The action that will be performed next
+    pub action: Option<Action>,
+
+    pub watches: Vec<(Arc<Expr>, Result<Value>)>,
+}
+
+/// One level of the call stack of a paused fiber
+#[derive(Clone, Debug)]
+pub struct FrameInfo {
+    pub actor: Entity,
+    pub fiber: u64,
+
+    /// The method this frame is running, or None for a top-level script
+    pub method: Option<Arc<str>>,
+
+    pub pc: usize,
+    pub locals: BTreeMap<Arc<str>, Value>,
+}
+
+#[derive(Default)]
+pub(crate) struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<Arc<Expr>>,
+    paused: Option<Pause>,
+
+    /// Lets the action we paused before run without pausing again
+    skip_once: bool,
+
+    steps_left: Option<usize>,
+}
+
+impl Breakpoint {
+    fn matches(&self, now: Instant, fiber: &Fiber, action: &Action) -> bool {
+        let action = action.unannotated();
+
+        match self {
+            Breakpoint::Action { keyword } => action.keyword() == keyword.as_ref(),
+
+            &Breakpoint::Actor { id } => fiber.me == id,
+
+            Breakpoint::Signal { head } => {
+                let woken = fiber.woken_by.as_ref()
+                    .is_some_and(|(signal, _)| &signal.head == head);
+
+                woken || match action {
+                    Action::ListenFor { head: other, .. } => other == head,
+                    Action::Transmit { head: other, .. } => other == head,
+                    _ => false,
+                }
+            },
+
+            &Breakpoint::Instant { at } => now >= at,
+        }
+    }
+}
+
+impl Workspace {
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.debugger.breakpoints.push(breakpoint);
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) {
+        self.debugger.breakpoints.retain(|other| other != breakpoint);
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.debugger.breakpoints
+    }
+
+    /// Evaluate `expr` in the paused fiber every time execution pauses
+    pub fn add_watch(&mut self, expr: Expr) {
+        self.debugger.watches.push(expr.into());
+    }
+
+    pub fn clear_watches(&mut self) {
+        self.debugger.watches.clear();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.debugger.paused.is_some()
+    }
+
+    pub fn paused(&self) -> Option<&Pause> {
+        self.debugger.paused.as_ref()
+    }
+
+    /// Perform a single action, then pause again
+    pub fn step(&mut self) -> Result<()> {
+        if self.active.is_empty() {
+            if self.has_halted {
+                return Ok(());
+            }
+
+            self.begin_task()?;
+        }
+
+        self.unpause();
+        self.debugger.skip_once = true;
+        self.debugger.steps_left = Some(1);
+
+        let outcome = self.drive(0);
+        self.debugger.steps_left = None;
+        outcome.map(|_finished| ())
+    }
+
+    /// Carry on simulating until the next breakpoint, or until halted
+    pub fn resume(&mut self) -> Result<()> {
+        self.debugger.steps_left = None;
+        self.update()?;
+        self.simulate()
+    }
+
+    /// Every frame of every active fiber, innermost last
+    pub fn call_stack(&self) -> Vec<FrameInfo> {
+        self.active.iter().flat_map(|fiber| {
+            fiber.stack.iter().map(move |frame| FrameInfo {
+                actor: fiber.me,
+                fiber: fiber.id,
+                method: frame.method.clone(),
+                pc: frame.pc,
+                locals: frame.locals.iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+            })
+        }).collect()
+    }
+
+    /// Evaluate an expression in the innermost active fiber, if there is one
+    pub fn inspect(&mut self, expr: &Expr) -> Result<Value> {
+        match self.active.pop() {
+            Some(fiber) => {
+                let value = self.eval_aside(&fiber, expr);
+                self.active.push(fiber);
+                value
+            },
+
+            None => self.evaluate(expr),
+        }
+    }
+
+    pub(crate) fn check_pause(&mut self, fiber: &Fiber) -> Option<PauseReason> {
+        let action = fiber.peek()?;
+
+        if !std::mem::take(&mut self.debugger.skip_once) {
+            let now = self.now;
+            let breakpoints = &mut self.debugger.breakpoints;
+
+            if let Some(hit) = breakpoints.iter().position(|bp| bp.matches(now, fiber, action)) {
+                let breakpoint = match breakpoints[hit] {
+                    Breakpoint::Instant { .. } => breakpoints.remove(hit),
+                    _ => breakpoints[hit].clone(),
+                };
+
+                return Some(PauseReason::Breakpoint(breakpoint));
+            }
+        }
+
+        match self.debugger.steps_left {
+            Some(0) => Some(PauseReason::Step),
+            Some(n) => {
+                self.debugger.steps_left = Some(n - 1);
+                None
+            },
+            None => None,
+        }
+    }
+
+    /// Stop with the innermost active fiber about to perform its next action
+    pub(crate) fn pause(&mut self, reason: PauseReason) {
+        let fiber = self.active.pop().unwrap();
+
+        let watches = self.debugger.watches.clone().into_iter().map(|expr| {
+            let value = self.eval_aside(&fiber, &expr);
+            (expr, value)
+        }).collect();
+
+        self.debugger.paused = Some(Pause {
+            reason,
+            time: self.now,
+            actor: fiber.me,
+            fiber: fiber.id,
+            action: fiber.peek().cloned(),
+            watches,
+        });
+
+        self.debugger.steps_left = None;
+        self.active.push(fiber);
+    }
+
+    pub(crate) fn unpause(&mut self) {
+        if self.debugger.paused.take().is_some() {
+            self.debugger.skip_once = true;
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::{Error, Liveness, Workspace};
+use crate::action::Action;
+use crate::history::EventKind;
+use crate::report::duration_text;
+use crate::time::Instant;
+
+/// How to run the same scripts many times over
+#[derive(Copy, Clone, Debug)]
+pub struct Ensemble {
+    pub runs: usize,
+
+    /// Run `i` is seeded with `first_seed + i`, wrapping around past `u64::MAX`
+    pub first_seed: u64,
+
+    pub threads: usize,
+
+    /// Stop each run here if it hasn't halted yet
+    pub until: Option<Instant>,
+}
+
+/// What happened in one run of an ensemble
+#[derive(Clone, Debug)]
+pub struct RunOutcome {
+    pub seed: u64,
+
+    /// When the run halted, or None if it failed or hit the time limit
+    pub halted_at: Option<Instant>,
+
+    pub error: Option<Error>,
+
+    /// Names of the actors dead by the end
+    pub dead: BTreeSet<Arc<str>>,
+
+    /// When each signal head was first received by anyone
+    pub first_received: BTreeMap<Arc<str>, Instant>,
+}
+
+/// Every run of an ensemble, in seed order
+#[derive(Clone, Debug)]
+pub struct EnsembleReport {
+    pub outcomes: Vec<RunOutcome>,
+}
+
+/// Counts of values falling into equal-width bins
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub start: f64,
+    pub width: f64,
+    pub counts: Vec<usize>,
+}
+
+impl Default for Ensemble {
+    fn default() -> Self {
+        Ensemble {
+            runs: 100,
+            first_seed: 0,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            until: None,
+        }
+    }
+}
+
+impl Ensemble {
+    /// Perform the scripts in order in a fresh workspace for every run, then
+    /// simulate each run to the end
+    pub fn run(&self, scripts: &[Arc<[Action]>]) -> EnsembleReport {
+        let next_run = AtomicUsize::new(0);
+        let outcomes = Mutex::new(Vec::with_capacity(self.runs));
+
+        std::thread::scope(|scope| {
+            for _ in 0 .. self.threads.clamp(1, self.runs.max(1)) {
+                scope.spawn(|| loop {
+                    let index = next_run.fetch_add(1, Ordering::Relaxed);
+                    if index >= self.runs {
+                        break;
+                    }
+
+                    let outcome = self.run_one(self.first_seed.wrapping_add(index as u64), scripts);
+                    outcomes.lock().unwrap().push(outcome);
+                });
+            }
+        });
+
+        let mut outcomes = outcomes.into_inner().unwrap();
+        // In run order, which differs from seed order once the seeds wrap
+        outcomes.sort_by_key(|outcome| outcome.seed.wrapping_sub(self.first_seed));
+        EnsembleReport { outcomes }
+    }
+
+    fn run_one(&self, seed: u64, scripts: &[Arc<[Action]>]) -> RunOutcome {
+        let mut workspace = Workspace::with_seed(seed);
+        workspace.set_echo(false);
+
+        let mut outcome = scripts.iter()
+            .try_for_each(|script| workspace.perform(script.clone()));
+
+        if outcome.is_ok() {
+            outcome = match self.until {
+                Some(limit) => workspace.simulate_until(limit),
+                None => workspace.simulate(),
+            };
+        }
+
+        let dead = workspace.globals().into_iter()
+            .filter(|&(_, id)| matches!(workspace.liveness_of(id), Liveness::Dead))
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut first_received = BTreeMap::new();
+        for event in workspace.history().events() {
+            if let EventKind::Received { signal, .. } = &event.kind {
+                first_received.entry(signal.head.clone()).or_insert(event.time);
+            }
+        }
+
+        RunOutcome {
+            seed,
+            halted_at: if outcome.is_ok() && workspace.has_halted() { Some(workspace.now()) } else { None },
+            error: outcome.err(),
+            dead,
+            first_received,
+        }
+    }
+}
+
+impl EnsembleReport {
+    /// Halt times of the runs that halted, in seconds, sorted
+    pub fn halt_times(&self) -> Vec<f64> {
+        sorted(self.outcomes.iter().filter_map(|outcome| outcome.halted_at))
+    }
+
+    /// Fraction of runs in which the named actor ended up dead
+    pub fn death_probability(&self, name: &str) -> f64 {
+        self.fraction(|outcome| outcome.dead.contains(name))
+    }
+
+    /// Times the signal head was first received, in seconds, sorted, leaving
+    /// out runs where it never was
+    pub fn first_receptions(&self, head: &str) -> Vec<f64> {
+        sorted(self.outcomes.iter().filter_map(|outcome| outcome.first_received.get(head).cloned()))
+    }
+
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| outcome.error.is_some()).count()
+    }
+
+    fn fraction(&self, test: impl Fn(&RunOutcome) -> bool) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+
+        self.outcomes.iter().filter(|outcome| test(outcome)).count() as f64 / self.outcomes.len() as f64
+    }
+}
+
+impl Histogram {
+    /// Spread the values over `bins` bins from the smallest to the largest
+    pub fn of(values: &[f64], bins: usize) -> Self {
+        let bins = bins.max(1);
+
+        if values.is_empty() {
+            return Histogram { start: 0.0, width: 0.0, counts: vec![0; bins] };
+        }
+
+        let start = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let end = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let width = (end - start) / bins as f64;
+        let mut counts = vec![0; bins];
+
+        for &value in values {
+            let bin = if width > 0.0 { ((value - start) / width) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        Histogram { start, width, counts }
+    }
+}
+
+fn sorted(times: impl Iterator<Item=Instant>) -> Vec<f64> {
+    let mut times = times.map(f64::from).collect::<Vec<_>>();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times
+}
+
+/// Summarize an ensemble: halt times, who died how often, and when each
+/// signal first got through
+pub fn write_summary(report: &EnsembleReport, bins: usize, out: &mut dyn Write) -> io::Result<()> {
+    let runs = report.outcomes.len();
+    writeln!(out, "runs: {}, failed: {}", runs, report.failures())?;
+
+    let halts = report.halt_times();
+    writeln!(out, "\nhalted in {} of {} runs", halts.len(), runs)?;
+    write_spread(&halts, bins, out)?;
+
+    let names = report.outcomes.iter()
+        .flat_map(|outcome| outcome.dead.iter().cloned())
+        .collect::<BTreeSet<_>>();
+
+    if !names.is_empty() {
+        writeln!(out, "\ndeath probability:")?;
+
+        for name in names {
+            writeln!(out, "  {:<24} {:>5.1}%", name, report.death_probability(&name) * 100.0)?;
+        }
+    }
+
+    let heads = report.outcomes.iter()
+        .flat_map(|outcome| outcome.first_received.keys().cloned())
+        .collect::<BTreeSet<_>>();
+
+    for head in heads {
+        let times = report.first_receptions(&head);
+        writeln!(out, "\n#{} first received in {} of {} runs", head, times.len(), runs)?;
+        write_spread(&times, bins, out)?;
+    }
+
+    Ok(())
+}
+
+fn write_spread(values: &[f64], bins: usize, out: &mut dyn Write) -> io::Result<()> {
+    const BAR_WIDTH: usize = 40;
+
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    writeln!(
+        out,
+        "  min {}, median {}, mean {}, max {}",
+        duration_text(values[0]),
+        duration_text(values[values.len() / 2]),
+        duration_text(mean),
+        duration_text(values[values.len() - 1]),
+    )?;
+
+    let histogram = Histogram::of(values, bins);
+    let most = histogram.counts.iter().cloned().max().unwrap_or(0).max(1);
+
+    for (bin, &count) in histogram.counts.iter().enumerate() {
+        let from = histogram.start + histogram.width * bin as f64;
+        let bar = "#".repeat((count * BAR_WIDTH).div_ceil(most));
+        let line = format!("  {:>10} {:>6} {}", duration_text(from), count, bar);
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use specs::Entity;
+use vek::Vec3;
+
+use crate::{Position, Trajectory, Workspace};
+use crate::history::Segment;
+use crate::report::json_string;
+use crate::time::{Instant, Interval};
+use crate::travel::DriveProfile;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SampleFormat {
+    Csv,
+    Json,
+}
+
+/// Which instants to sample, from `from` to `until` inclusive
+#[derive(Copy, Clone, Debug)]
+pub struct Sampling {
+    pub from: Instant,
+    pub until: Instant,
+    pub step: Interval,
+}
+
+/// One actor's state at one instant
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub time: Instant,
+    pub actor: Entity,
+    pub position: Position,
+    pub velocity: Vec3<f64>,
+    pub alive: bool,
+}
+
+impl FromStr for SampleFormat {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src {
+            "csv" => Ok(SampleFormat::Csv),
+            "json" => Ok(SampleFormat::Json),
+            other => Err(format!("unknown sample format: {}", other)),
+        }
+    }
+}
+
+impl Sampling {
+    /// Cover the whole recorded history in roughly `count` steps
+    pub fn across(workspace: &Workspace, count: usize) -> Self {
+        let from = Instant::default();
+        let until = workspace.now();
+        let span = f64::from(from.delta(until));
+
+        Sampling {
+            from,
+            until,
+            step: Interval::from_f64((span / count.max(1) as f64).max(1.0)),
+        }
+    }
+
+    fn instants(&self) -> impl Iterator<Item=Instant> + '_ {
+        let step = f64::from(self.step);
+        let count = if step > 0.0 {
+            (f64::from(self.from.delta(self.until)) / step).floor().max(0.0) as usize + 1
+        } else {
+            1
+        };
+
+        // Offsets are computed from the start, so rounding errors don't pile up
+        (0 .. count).map(move |index| self.from + Interval::from_f64(step * index as f64))
+    }
+}
+
+/// Sample every actor that has ever moved at each instant, skipping actors
+/// not created yet
+pub fn sample(workspace: &Workspace, sampling: &Sampling) -> Vec<Sample> {
+    let history = workspace.history();
+    let actors = &tracked_actors(workspace);
+    let deaths = &deaths(workspace);
+
+    sampling.instants().flat_map(|time| {
+        actors.iter().filter_map(move |&(_, actor)| {
+            if workspace.creation_date_of(actor).is_some_and(|created| time < created) {
+                return None;
+            }
+
+            let velocity = history.trajectory_at(actor, time)
+                .map_or(Vec3::zero(), |trajectory| trajectory.velocity_at(time));
+
+            Some(Sample {
+                time,
+                actor,
+                position: history.place_of(actor, time),
+                velocity,
+                alive: deaths.get(&actor).is_none_or(|&died| time < died),
+            })
+        })
+    }).collect()
+}
+
+pub fn write_samples(
+    workspace: &Workspace,
+    sampling: &Sampling,
+    format: SampleFormat,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let samples = sample(workspace, sampling);
+
+    match format {
+        SampleFormat::Csv => {
+            writeln!(out, "time,actor,x,y,z,vx,vy,vz,alive")?;
+
+            for sample in samples.iter() {
+                let (p, v) = (sample.position.0, sample.velocity);
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{}",
+                    f64::from(sample.time),
+                    csv_field(&actor_name(workspace, sample.actor)),
+                    p.x, p.y, p.z,
+                    v.x, v.y, v.z,
+                    sample.alive,
+                )?;
+            }
+        },
+
+        SampleFormat::Json => {
+            writeln!(out, "[")?;
+
+            for (index, sample) in samples.iter().enumerate() {
+                let separator = if index + 1 < samples.len() { "," } else { "" };
+                writeln!(
+                    out,
+                    "  {{\"time\": {}, \"actor\": {}, \"position\": {}, \"velocity\": {}, \"alive\": {}}}{}",
+                    num_json(f64::from(sample.time))?,
+                    json_string(&actor_name(workspace, sample.actor)),
+                    vec_json(sample.position.0)?,
+                    vec_json(sample.velocity)?,
+                    sample.alive,
+                    separator,
+                )?;
+            }
+
+            writeln!(out, "]")?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Write the exact piecewise trajectory of every actor as JSON. Numbers are
+/// written with enough digits to read back the same f64s.
+pub fn write_segments(workspace: &Workspace, out: &mut dyn Write) -> io::Result<()> {
+    let history = workspace.history();
+    let actors = tracked_actors(workspace);
+    let deaths = deaths(workspace);
+
+    writeln!(out, "[")?;
+
+    for (index, (name, actor)) in actors.iter().enumerate() {
+        let died = match deaths.get(actor) {
+            Some(&time) => num_json(f64::from(time))?,
+            None => "null".into(),
+        };
+
+        let created = match workspace.creation_date_of(*actor) {
+            Some(time) => num_json(f64::from(time))?,
+            None => "null".into(),
+        };
+
+        writeln!(out, "  {{\"actor\": {}, \"created\": {}, \"died\": {}, \"segments\": [", json_string(name), created, died)?;
+
+        let segments = history.segments_of(*actor);
+        for (index, segment) in segments.iter().enumerate() {
+            let separator = if index + 1 < segments.len() { "," } else { "" };
+            writeln!(out, "    {{\"since\": {}, {}}}{}", num_json(f64::from(segment.since))?, trajectory_json(&segment.trajectory)?, separator)?;
+        }
+
+        let separator = if index + 1 < actors.len() { "," } else { "" };
+        writeln!(out, "  ]}}{}", separator)?;
+    }
+
+    writeln!(out, "]")
+}
+
+fn trajectory_json(trajectory: &Trajectory) -> io::Result<String> {
+    Ok(match *trajectory {
+        Trajectory::Fixed { value } => {
+            format!("\"kind\": \"fixed\", \"place\": {}", vec_json(value.0)?)
+        },
+
+        Trajectory::Linear { start_place, start_time, start_velocity, accel } => {
+            format!(
+                "\"kind\": \"linear\", \"start_place\": {}, \"start_time\": {}, \"start_velocity\": {}, \"accel\": {}",
+                vec_json(start_place.0)?,
+                num_json(f64::from(start_time))?,
+                vec_json(start_velocity)?,
+                vec_json(accel)?,
+            )
+        },
+
+        Trajectory::Transfer { start_place, start_time, end_place, profile } => {
+            let profile = match profile {
+                DriveProfile::ThrustBrake { accel } => {
+                    format!("{{\"kind\": \"thrust-brake\", \"accel\": {}}}", num_json(accel)?)
+                },
+
+                DriveProfile::ThrustCoast { accel, max_speed } => {
+                    format!("{{\"kind\": \"thrust-coast\", \"accel\": {}, \"max_speed\": {}}}", num_json(accel)?, num_json(max_speed)?)
+                },
+            };
+
+            format!(
+                "\"kind\": \"transfer\", \"start_place\": {}, \"start_time\": {}, \"end_place\": {}, \"profile\": {}",
+                vec_json(start_place.0)?,
+                num_json(f64::from(start_time))?,
+                vec_json(end_place.0)?,
+                profile,
+            )
+        },
+    })
+}
+
+/// One actor's course, as read back from what `write_segments` wrote
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActorSegments {
+    pub actor: String,
+    pub created: Option<Instant>,
+    pub died: Option<Instant>,
+    pub segments: Vec<Segment>,
+}
+
+/// Read back the output of `write_segments`, with every number exactly as
+/// it was
+pub fn read_segments(src: &str) -> Result<Vec<ActorSegments>, String> {
+    let mut reader = JsonReader { src, at: 0 };
+    let json = reader.value()?;
+    reader.skip_space();
+
+    if reader.at < src.len() {
+        return Err(format!("unexpected text at byte {}", reader.at));
+    }
+
+    json.items()?.iter().map(|actor| {
+        Ok(ActorSegments {
+            actor: actor.field("actor")?.text()?.to_owned(),
+            created: actor.field("created")?.optional(Json::instant)?,
+            died: actor.field("died")?.optional(Json::instant)?,
+            segments: actor.field("segments")?.items()?.iter().map(|segment| {
+                Ok(Segment {
+                    since: segment.field("since")?.instant()?,
+                    trajectory: segment.trajectory()?,
+                })
+            }).collect::<Result<_, String>>()?,
+        })
+    }).collect()
+}
+
+/// Just enough JSON to read back what this module writes
+#[derive(Debug)]
+enum Json {
+    Null,
+    Num(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn field(&self, name: &str) -> Result<&Json, String> {
+        match self {
+            Json::Object(fields) => fields.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("missing field {:?}", name)),
+            other => Err(format!("expected an object, got {:?}", other)),
+        }
+    }
+
+    fn items(&self) -> Result<&[Json], String> {
+        match self {
+            Json::Array(items) => Ok(items),
+            other => Err(format!("expected an array, got {:?}", other)),
+        }
+    }
+
+    fn text(&self) -> Result<&str, String> {
+        match self {
+            Json::Str(text) => Ok(text),
+            other => Err(format!("expected a string, got {:?}", other)),
+        }
+    }
+
+    fn num(&self) -> Result<f64, String> {
+        match *self {
+            Json::Num(n) => Ok(n),
+            ref other => Err(format!("expected a number, got {:?}", other)),
+        }
+    }
+
+    fn instant(&self) -> Result<Instant, String> {
+        Ok(Instant::default() + Interval::from_f64(self.num()?))
+    }
+
+    fn vector(&self) -> Result<Vec3<f64>, String> {
+        match self.items()? {
+            [x, y, z] => Ok(Vec3::new(x.num()?, y.num()?, z.num()?)),
+            other => Err(format!("expected three coordinates, got {}", other.len())),
+        }
+    }
+
+    fn optional<T>(&self, read: impl Fn(&Json) -> Result<T, String>) -> Result<Option<T>, String> {
+        match self {
+            Json::Null => Ok(None),
+            other => read(other).map(Some),
+        }
+    }
+
+    fn trajectory(&self) -> Result<Trajectory, String> {
+        let place = |name| self.field(name)?.vector().map(Position);
+
+        match self.field("kind")?.text()? {
+            "fixed" => Ok(Trajectory::Fixed { value: place("place")? }),
+
+            "linear" => Ok(Trajectory::Linear {
+                start_place: place("start_place")?,
+                start_time: self.field("start_time")?.instant()?,
+                start_velocity: self.field("start_velocity")?.vector()?,
+                accel: self.field("accel")?.vector()?,
+            }),
+
+            "transfer" => {
+                let profile = self.field("profile")?;
+                let accel = profile.field("accel")?.num()?;
+
+                let profile = match profile.field("kind")?.text()? {
+                    "thrust-brake" => DriveProfile::ThrustBrake { accel },
+                    "thrust-coast" => DriveProfile::ThrustCoast { accel, max_speed: profile.field("max_speed")?.num()? },
+                    other => return Err(format!("unknown drive profile {:?}", other)),
+                };
+
+                Ok(Trajectory::Transfer {
+                    start_place: place("start_place")?,
+                    start_time: self.field("start_time")?.instant()?,
+                    end_place: place("end_place")?,
+                    profile,
+                })
+            },
+
+            other => Err(format!("unknown trajectory kind {:?}", other)),
+        }
+    }
+}
+
+struct JsonReader<'a> {
+    src: &'a str,
+    at: usize,
+}
+
+impl JsonReader<'_> {
+    fn skip_space(&mut self) {
+        let rest = &self.src[self.at ..];
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.src[self.at ..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.at += c.len_utf8();
+                Ok(())
+            },
+            found => Err(format!("expected {:?} at byte {}, found {:?}", c, self.at, found)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some('{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+
+                if self.peek() != Some('}') {
+                    loop {
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        fields.push((key, self.value()?));
+
+                        if self.peek() != Some(',') {
+                            break;
+                        }
+                        self.at += 1;
+                    }
+                }
+
+                self.expect('}')?;
+                Ok(Json::Object(fields))
+            },
+
+            Some('[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+
+                if self.peek() != Some(']') {
+                    loop {
+                        items.push(self.value()?);
+
+                        if self.peek() != Some(',') {
+                            break;
+                        }
+                        self.at += 1;
+                    }
+                }
+
+                self.expect(']')?;
+                Ok(Json::Array(items))
+            },
+
+            Some('"') => self.string().map(Json::Str),
+
+            Some(_) => {
+                let rest = &self.src[self.at ..];
+                let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c))).unwrap_or(rest.len());
+                let word = &rest[.. len];
+                self.at += len;
+
+                match word {
+                    "null" => Ok(Json::Null),
+                    // JSON numbers are always finite, so words like `NaN` and `inf` are out
+                    number => number.parse().ok()
+                        .filter(|n: &f64| n.is_finite())
+                        .map(Json::Num)
+                        .ok_or_else(|| format!("bad value {:?} at byte {}", number, self.at - len)),
+                }
+            },
+
+            None => Err("unexpected end of input".into()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        let mut chars = self.src[self.at ..].char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.at += offset + 1;
+                    return Ok(text);
+                },
+
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('u') => {
+                        let hex = chars.by_ref().take(4).map(|(_, c)| c).collect::<String>();
+                        let code = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                            .ok_or_else(|| format!("bad escape \\u{}", hex))?;
+                        text.push(code);
+                    },
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+
+                c => text.push(c),
+            }
+        }
+
+        Err("unterminated string".into())
+    }
+}
+
+/// Named actors with at least one trajectory, sorted by name
+fn tracked_actors(workspace: &Workspace) -> Vec<(String, Entity)> {
+    workspace.globals().into_iter()
+        .filter(|&(_, actor)| !workspace.history().segments_of(actor).is_empty())
+        .map(|(name, actor)| (name.to_string(), actor))
+        .collect()
+}
+
+fn deaths(workspace: &Workspace) -> HashMap<Entity, Instant> {
+    let mut deaths = HashMap::new();
+
+    for event in workspace.history().events() {
+        if event.kind.is_death() {
+            deaths.entry(event.actor).or_insert(event.time);
+        }
+    }
+
+    deaths
+}
+
+fn actor_name(workspace: &Workspace, actor: Entity) -> String {
+    match workspace.name_of(actor) {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", actor),
+    }
+}
+
+// f64's Display is the shortest text that parses back to the same value.
+// JSON has no infinities or NaN, so those are refused rather than written.
+fn num_json(n: f64) -> io::Result<String> {
+    if !n.is_finite() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} cannot be written as JSON", n)));
+    }
+
+    Ok(format!("{}", n))
+}
+
+fn vec_json(v: Vec3<f64>) -> io::Result<String> {
+    Ok(format!("[{}, {}, {}]", num_json(v.x)?, num_json(v.y)?, num_json(v.z)?))
+}
+
+fn csv_field(src: &str) -> String {
+    if src.contains([',', '"', '\n']) {
+        format!("\"{}\"", src.replace('"', "\"\""))
+    } else {
+        src.into()
+    }
+}
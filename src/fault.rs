@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+use specs::prelude::*;
+
+use crate::{Error, Result, Workspace};
+use crate::action::{Signal, Value};
+use crate::history::{Event, EventKind};
+
+/// What happens when one of a fiber's actions fails
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FaultPolicy {
+    /// Stop simulating and hand the error back
+    #[default]
+    Abort,
+
+    /// Drop the failing fiber, and let everything else carry on
+    KillFiber,
+
+    /// Drop the failing fiber, and mark its actor dead with nothing left to do
+    KillActor,
+}
+
+impl FromStr for FaultPolicy {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src {
+            "abort" => Ok(FaultPolicy::Abort),
+            "kill-fiber" => Ok(FaultPolicy::KillFiber),
+            "kill-actor" => Ok(FaultPolicy::KillActor),
+            other => Err(format!("unknown fault policy: {}", other)),
+        }
+    }
+}
+
+impl Workspace {
+    pub fn set_fault_policy(&mut self, policy: FaultPolicy) {
+        self.fault_policy = policy;
+    }
+
+    pub fn fault_policy(&self) -> FaultPolicy {
+        self.fault_policy
+    }
+
+    /// Have `supervisor` hear `#fault(actor)` whenever a failure is isolated,
+    /// if it is listening for that actor
+    pub fn set_fault_supervisor(&mut self, supervisor: Option<Entity>) {
+        self.fault_supervisor = supervisor;
+    }
+
+    /// Record a failure the policy keeps from stopping the simulation, and
+    /// deal with the failing actor as it says. The fiber is already gone.
+    pub(crate) fn isolate(&mut self, error: Error) -> Result<()> {
+        let (actor, fiber) = match error.backtrace() {
+            Some(backtrace) => (backtrace.actor, backtrace.fiber),
+            None => return Err(error),
+        };
+
+        if self.echo {
+            eprintln!("{:<8.0}: failed: {}", f64::from(self.now), error.root());
+        }
+
+        let failure = self.history.record(Event {
+            time: self.now,
+            actor,
+            fiber,
+            kind: EventKind::Failed { error },
+            annotation: self.note.clone(),
+        });
+
+        if self.fault_policy == FaultPolicy::KillActor {
+            self.kill(actor)?;
+
+            self.history.record(Event {
+                time: self.now,
+                actor,
+                fiber,
+                kind: EventKind::Killed { failure },
+                annotation: None,
+            });
+        }
+
+        if let Some(supervisor) = self.fault_supervisor {
+            let signal = Signal { head: "fault".into(), body: vec![Value::ActorId(actor)].into() };
+
+            if let Some(listeners) = self.listeners.get_mut(&signal) {
+                listeners.remove(&supervisor);
+                if listeners.is_empty() {
+                    self.listeners.remove(&signal);
+                }
+            }
+
+            self.wake(supervisor, &signal, failure);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use specs::Entity;
+
+use crate::{Error, Position, Trajectory};
+use crate::causality::CausalIndex;
+use crate::action::{Action, Annotation, Expr, Signal, Value};
+use crate::random::Distribution;
+use crate::time::Instant;
+
+/// Index of an event within the history
+pub type EventId = usize;
+
+/// Something that happened during the simulation
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub time: Instant,
+    pub actor: Entity,
+    pub fiber: u64,
+    pub kind: EventKind,
+
+    /// Notes on the action that caused the event, if it had any
+    pub annotation: Option<Arc<Annotation>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum EventKind {
+    /// An action with no outcome worth recording beyond itself
+    Performed {
+        action: Action,
+    },
+
+    Traced {
+        expr: Arc<Expr>,
+        value: Value,
+    },
+
+    Spawned {
+        name: Arc<str>,
+        child: Entity,
+    },
+
+    Transmitted {
+        signal: Signal,
+    },
+
+    /// A listening fiber woke up because of an earlier transmission
+    Received {
+        signal: Signal,
+        transmission: EventId,
+    },
+
+    /// A random draw, with the arguments it was made from
+    Drew {
+        dist: Distribution,
+        args: Arc<[Value]>,
+        value: Value,
+    },
+
+    Halted,
+
+    /// An action failed, and the fault policy kept the simulation going
+    Failed {
+        error: Error,
+    },
+
+    /// The fault policy killed the actor over an earlier failure
+    Killed {
+        failure: EventId,
+    },
+}
+
+impl EventKind {
+    /// Whether the actor died here, by its own hand or the fault policy's
+    pub fn is_death(&self) -> bool {
+        matches!(self, EventKind::Performed { action: Action::Die } | EventKind::Killed { .. })
+    }
+}
+
+/// A stretch of an actor's path, followed from `since` until the next one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub since: Instant,
+    pub trajectory: Trajectory,
+}
+
+/// Every event recorded so far, in the order they happened
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    events: Vec<Event>,
+    fiber_origins: HashMap<u64, EventId>,
+    segments: HashMap<Entity, Vec<Segment>>,
+    causality: CausalIndex,
+}
+
+impl History {
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn get(&self, id: EventId) -> Option<&Event> {
+        self.events.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events caused by actions carrying the given tag
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item=(EventId, &'a Event)> + 'a {
+        self.events.iter().enumerate().filter(move |(_, event)| {
+            event.annotation.as_ref().is_some_and(|annotation| annotation.has_tag(tag))
+        })
+    }
+
+    /// Chapters in the order they first come up
+    pub fn chapters(&self) -> Vec<Arc<str>> {
+        let mut chapters: Vec<Arc<str>> = Vec::new();
+
+        for event in self.events.iter() {
+            if let Some(chapter) = event.annotation.as_ref().and_then(|annotation| annotation.chapter.as_ref()) {
+                if !chapters.contains(chapter) {
+                    chapters.push(chapter.clone());
+                }
+            }
+        }
+
+        chapters
+    }
+
+    /// The `as ... do` event that started a fiber, if any
+    pub fn fiber_origin(&self, fiber: u64) -> Option<EventId> {
+        self.fiber_origins.get(&fiber).cloned()
+    }
+
+    /// Every trajectory the actor has followed, oldest first
+    pub fn segments_of(&self, actor: Entity) -> &[Segment] {
+        self.segments.get(&actor).map_or(&[], |segments| segments.as_slice())
+    }
+
+    pub fn trajectory_at(&self, actor: Entity, time: Instant) -> Option<Trajectory> {
+        self.segments_of(actor).iter()
+            .take_while(|segment| segment.since <= time)
+            .last()
+            .map(|segment| segment.trajectory)
+    }
+
+    /// Where the actor was at the given time. Actors that never moved, like
+    /// the supervisor, sit at the origin.
+    pub fn place_of(&self, actor: Entity, time: Instant) -> Position {
+        self.trajectory_at(actor, time)
+            .or_else(|| self.segments_of(actor).first().map(|segment| segment.trajectory))
+            .unwrap_or_default()
+            .sample_at(time)
+    }
+
+    pub(crate) fn record_segment(&mut self, actor: Entity, since: Instant, trajectory: Trajectory) {
+        let segments = self.segments.entry(actor).or_default();
+
+        // Only the last trajectory set at any one instant counts
+        while segments.last().is_some_and(|segment| segment.since == since) {
+            segments.pop();
+        }
+
+        segments.push(Segment { since, trajectory });
+    }
+
+    pub(crate) fn record_fiber_origin(&mut self, fiber: u64, origin: EventId) {
+        self.fiber_origins.insert(fiber, origin);
+    }
+
+    pub(crate) fn record(&mut self, event: Event) -> EventId {
+        let id = self.events.len();
+        let origin = self.fiber_origin(event.fiber);
+        self.causality.add(id, &event, origin);
+        self.events.push(event);
+        id
+    }
+
+    pub(crate) fn causality(&self) -> &CausalIndex {
+        &self.causality
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::action::{Action, Condition, Expr, Method};
+use crate::saga::{self, ParseError};
+use crate::script::Script;
+
+/// Something that stopped a saga and its imports from loading
+#[derive(Debug)]
+pub enum ImportError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+
+    Parse {
+        path: PathBuf,
+        error: ParseError,
+    },
+
+    /// Each file imports the next, and the last imports the first
+    Cycle {
+        chain: Vec<PathBuf>,
+    },
+
+    /// Two different files imported under the same namespace
+    Conflict {
+        namespace: Arc<str>,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
+/// Loads saga files along with everything they import. Each imported file's
+/// actors and methods are renamed into its namespace, so `spawn Sol` in
+/// `stars.saga` defines `stars::Sol`. Files are loaded at most once per
+/// namespace, however many times they are imported.
+#[derive(Default)]
+pub struct Loader {
+    namespaces: HashMap<Arc<str>, PathBuf>,
+
+    /// Files being loaded right now, outermost first
+    loading: Vec<PathBuf>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Loader::default()
+    }
+
+    /// Read a top-level saga file, whose own names stay unqualified
+    pub fn load(&mut self, path: &Path) -> Result<Script, ImportError> {
+        let path = canonical(path)?;
+        self.nested(path, |loader, path, script| loader.resolve(script, base_of(path)))
+    }
+
+    /// Put the actions of everything `script` imports ahead of its own,
+    /// finding the imported files relative to `base`
+    pub fn resolve(&mut self, script: Script, base: &Path) -> Result<Script, ImportError> {
+        let mut body = Vec::new();
+
+        for import in script.imports() {
+            let path = canonical(&base.join(import.path.as_ref()))?;
+
+            let namespace = match &import.namespace {
+                Some(namespace) => namespace.clone(),
+                None => path.file_stem().unwrap_or_default().to_string_lossy().as_ref().into(),
+            };
+
+            if let Some(start) = self.loading.iter().position(|other| *other == path) {
+                let mut chain = self.loading[start ..].to_vec();
+                chain.push(path);
+                return Err(ImportError::Cycle { chain });
+            }
+
+            match self.namespaces.get(&namespace) {
+                Some(first) if *first == path => continue,
+
+                Some(first) => return Err(ImportError::Conflict {
+                    namespace,
+                    first: first.clone(),
+                    second: path,
+                }),
+
+                None => (),
+            }
+
+            let module = self.nested(path.clone(), |loader, path, script| loader.resolve(script, base_of(path)))?;
+            self.namespaces.insert(namespace.clone(), path);
+            body.extend(qualify(&module.body, &namespace));
+        }
+
+        body.extend(script.body.iter().cloned());
+        Ok(Script::new(body.into()))
+    }
+
+    /// Parse the file at `path` and hand it to `then`, with `path` on the
+    /// loading stack meanwhile
+    fn nested(
+        &mut self,
+        path: PathBuf,
+        then: impl FnOnce(&mut Self, &Path, Script) -> Result<Script, ImportError>,
+    ) -> Result<Script, ImportError> {
+        let src = std::fs::read_to_string(&path).map_err(|error| {
+            ImportError::Io { path: path.clone(), error }
+        })?;
+
+        let script = saga::parse(&src).map_err(|error| {
+            ImportError::Parse { path: path.clone(), error }
+        })?;
+
+        self.loading.push(path.clone());
+        let result = then(self, &path, script);
+        self.loading.pop();
+        result
+    }
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, ImportError> {
+    path.canonicalize().map_err(|error| ImportError::Io { path: path.into(), error })
+}
+
+fn base_of(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new("."))
+}
+
+/// Names a module defines for itself, as opposed to ones it borrows
+#[derive(Default)]
+struct Declared {
+    actors: HashSet<Arc<str>>,
+    methods: HashSet<Arc<str>>,
+    locals: HashSet<Arc<str>>,
+}
+
+impl Declared {
+    fn collect(&mut self, script: &[Action]) {
+        for action in script {
+            match action {
+                Action::Spawn { name } => {
+                    self.actors.insert(name.clone());
+                },
+
+                Action::AsActor { script, .. } => self.collect(script),
+                Action::Every { script, .. } => self.collect(script),
+
+                Action::WriteLocal { name, .. } => {
+                    self.locals.insert(name.clone());
+                },
+
+                Action::DefGlobalMethod { name, body } => {
+                    self.methods.insert(name.clone());
+                    self.locals.extend(body.params.iter().cloned());
+                    self.collect(&body.script);
+                },
+
+                Action::Annotated { action, .. } => self.collect(std::slice::from_ref(action.as_ref())),
+
+                _ => (),
+            }
+        }
+    }
+
+    fn is_actor(&self, name: &str) -> bool {
+        self.actors.contains(name) && !self.locals.contains(name)
+    }
+}
+
+/// Rename the actors and methods a module defines into its namespace
+fn qualify(script: &[Action], namespace: &str) -> Vec<Action> {
+    let mut declared = Declared::default();
+    declared.collect(script);
+
+    // Names from the module's own imports are qualified already
+    declared.actors.retain(|name| !name.contains("::"));
+    declared.methods.retain(|name| !name.contains("::"));
+
+    let renamer = Renamer { namespace, declared };
+    renamer.script(script)
+}
+
+struct Renamer<'a> {
+    namespace: &'a str,
+    declared: Declared,
+}
+
+impl<'a> Renamer<'a> {
+    fn name(&self, name: &Arc<str>) -> Arc<str> {
+        format!("{}::{}", self.namespace, name).into()
+    }
+
+    fn actor(&self, name: &Arc<str>) -> Arc<str> {
+        if self.declared.is_actor(name) { self.name(name) } else { name.clone() }
+    }
+
+    fn method(&self, name: &Arc<str>) -> Arc<str> {
+        if self.declared.methods.contains(name) { self.name(name) } else { name.clone() }
+    }
+
+    fn script(&self, script: &[Action]) -> Vec<Action> {
+        script.iter().map(|action| self.action(action)).collect()
+    }
+
+    fn exprs(&self, exprs: &[Expr]) -> Arc<[Expr]> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn action(&self, action: &Action) -> Action {
+        match action {
+            Action::Trace { expr } => Action::Trace {
+                expr: self.expr(expr).into(),
+            },
+
+            Action::Spawn { name } => Action::Spawn {
+                name: self.actor(name),
+            },
+
+            Action::Every { interval, from, until, script } => Action::Every {
+                interval: *interval,
+                from: *from,
+                until: *until,
+                script: self.script(script).into(),
+            },
+
+            Action::WaitNear { subject, distance, target } => Action::WaitNear {
+                subject: self.expr(subject).into(),
+                distance: self.expr(distance).into(),
+                target: self.expr(target).into(),
+            },
+
+            Action::ListenFor { head, args } => Action::ListenFor {
+                head: head.clone(),
+                args: self.exprs(args),
+            },
+
+            Action::AsActor { name, script } => Action::AsActor {
+                name: self.actor(name),
+                script: self.script(script).into(),
+            },
+
+            Action::TravelTo { target, profile } => Action::TravelTo {
+                target: self.expr(target).into(),
+                profile: *profile,
+            },
+
+            Action::Transmit { head, args } => Action::Transmit {
+                head: head.clone(),
+                args: self.exprs(args),
+            },
+
+            Action::WriteLocal { name, value } => Action::WriteLocal {
+                name: name.clone(),
+                value: self.expr(value).into(),
+            },
+
+            Action::DefGlobalMethod { name, body } => Action::DefGlobalMethod {
+                name: self.method(name),
+                body: Method {
+                    params: body.params.clone(),
+                    script: self.script(&body.script).into(),
+                }.into(),
+            },
+
+            Action::Call { name, args } => Action::Call {
+                name: self.method(name),
+                args: self.exprs(args),
+            },
+
+            Action::Board { container } => Action::Board {
+                container: self.expr(container).into(),
+            },
+
+            Action::If { condition, skip } => Action::If {
+                condition: self.condition(condition).into(),
+                skip: *skip,
+            },
+
+            Action::Annotated { action, annotation } => Action::Annotated {
+                action: self.action(action).into(),
+                annotation: annotation.clone(),
+            },
+
+            other => other.clone(),
+        }
+    }
+
+    fn condition(&self, condition: &Condition) -> Condition {
+        match condition {
+            Condition::Knows { head, args } => Condition::Knows {
+                head: head.clone(),
+                args: self.exprs(args),
+            },
+
+            Condition::Not { condition } => Condition::Not {
+                condition: self.condition(condition).into(),
+            },
+        }
+    }
+
+    fn expr(&self, expr: &Expr) -> Expr {
+        match expr {
+            Expr::Field { subject, field_name } => Expr::Field {
+                subject: self.expr(subject).into(),
+                field_name: field_name.clone(),
+            },
+
+            Expr::Var { name } => Expr::Var {
+                name: self.actor(name),
+            },
+
+            Expr::Builtin { func, args } => Expr::Builtin {
+                func: *func,
+                args: self.exprs(args),
+            },
+
+            Expr::Random { dist, args } => Expr::Random {
+                dist: *dist,
+                args: self.exprs(args),
+            },
+
+            Expr::Within { center, radius, tag } => Expr::Within {
+                center: self.expr(center).into(),
+                radius: self.expr(radius).into(),
+                tag: tag.clone(),
+            },
+
+            Expr::Nearest { center, count, tag } => Expr::Nearest {
+                center: self.expr(center).into(),
+                count: self.expr(count).into(),
+                tag: tag.clone(),
+            },
+
+            other => other.clone(),
+        }
+    }
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io { path, error } => {
+                write!(f, "cannot read {}: {}", path.display(), error)
+            },
+
+            ImportError::Parse { path, error } => {
+                write!(f, "{}: {}", path.display(), error)
+            },
+
+            ImportError::Cycle { chain } => {
+                write!(f, "import cycle: {}", chain.iter().map(|path| {
+                    path.display().to_string()
+                }).collect::<Vec<_>>().join(" -> "))
+            },
+
+            ImportError::Conflict { namespace, first, second } => {
+                write!(f, "both {} and {} imported as `{}`", first.display(), second.display(), namespace)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
@@ -0,0 +1,113 @@
+use specs::prelude::*;
+use specs::{Component, VecStorage};
+
+use crate::{Position, Result, Workspace};
+use crate::action::{Condition, Signal, Value};
+use crate::history::EventId;
+use crate::task::Fiber;
+use crate::time::Instant;
+
+/// Something an actor can know
+#[derive(Clone, Debug)]
+pub enum Fact {
+    /// The contents of a signal, sent or received
+    Signal {
+        signal: Signal,
+    },
+
+    /// Where an actor was at `observed_at`, which is usually well before the
+    /// news arrived
+    Position {
+        actor: Entity,
+        place: Position,
+        observed_at: Instant,
+    },
+}
+
+/// A fact, and how the actor came to know it
+#[derive(Clone, Debug)]
+pub struct Known {
+    pub fact: Fact,
+    pub learned_at: Instant,
+
+    /// The event that taught it
+    pub source: EventId,
+}
+
+/// Everything an actor has learned, oldest first
+#[derive(Clone, Default, Component)]
+#[storage(VecStorage)]
+pub struct Knowledge {
+    known: Vec<Known>,
+}
+
+impl Workspace {
+    /// Everything the actor has learned so far
+    pub fn knowledge_of(&self, actor: Entity) -> Vec<Known> {
+        self.world.read_component::<Knowledge>().get(actor)
+            .map(|knowledge| knowledge.known.clone())
+            .unwrap_or_default()
+    }
+
+    /// What the actor knew at the given time
+    pub fn knew_at(&self, actor: Entity, time: Instant) -> Vec<Known> {
+        let mut known = self.knowledge_of(actor);
+        known.retain(|known| known.learned_at <= time);
+        known
+    }
+
+    /// Whether the actor has heard, or sent, this exact signal
+    pub fn knows(&self, actor: Entity, signal: &Signal) -> bool {
+        self.world.read_component::<Knowledge>().get(actor).is_some_and(|knowledge| {
+            knowledge.known.iter().any(|known| match &known.fact {
+                Fact::Signal { signal: other } => other == signal,
+                _ => false,
+            })
+        })
+    }
+
+    /// Where the actor last knew `other` to be, and as of when
+    pub fn last_seen(&self, actor: Entity, other: Entity) -> Option<(Position, Instant)> {
+        self.knowledge_of(actor).into_iter()
+            .filter_map(|known| match known.fact {
+                Fact::Position { actor, place, observed_at } if actor == other => Some((place, observed_at)),
+                _ => None,
+            })
+            .max_by_key(|&(_, observed_at)| observed_at)
+    }
+
+    pub(crate) fn learn(&mut self, actor: Entity, fact: Fact, source: EventId) {
+        let known = Known { fact, learned_at: self.now, source };
+
+        if let Ok(entry) = self.world.write_component::<Knowledge>().entry(actor) {
+            entry.or_insert_with(Knowledge::default).known.push(known);
+        }
+    }
+
+    /// Learn a received signal, and where its sender was when sending it
+    pub(crate) fn learn_reception(&mut self, actor: Entity, signal: Signal, transmission: EventId, reception: EventId) {
+        let (sender, sent_at) = match self.history.get(transmission) {
+            Some(event) => (event.actor, event.time),
+            None => return,
+        };
+
+        let place = self.history.place_of(sender, sent_at);
+        self.learn(actor, Fact::Signal { signal }, reception);
+        self.learn(actor, Fact::Position { actor: sender, place, observed_at: sent_at }, reception);
+    }
+
+    pub(crate) fn check_condition(&mut self, fiber: &Fiber, condition: &Condition) -> Result<bool> {
+        Ok(match condition {
+            Condition::Knows { head, args } => {
+                let body = args.iter().map(|arg| {
+                    self.eval_expr(fiber, arg)
+                }).collect::<Result<Vec<Value>>>()?;
+
+                let signal = Signal { head: head.clone(), body: body.into() };
+                self.knows(fiber.me, &signal)
+            },
+
+            Condition::Not { condition } => !self.check_condition(fiber, condition)?,
+        })
+    }
+}
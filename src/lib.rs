@@ -1,16 +1,51 @@
 pub mod action;
+pub mod backtrace;
+pub mod boarding;
+pub mod builtins;
+pub mod causality;
+pub mod chronicle;
+pub mod continuity;
+pub mod deadlock;
+pub mod debug;
+pub mod ensemble;
+pub mod fault;
+pub mod export;
+pub mod history;
+pub mod import;
+pub mod knowledge;
+pub mod map;
 pub mod time;
 pub mod task;
 pub mod script;
+pub mod timeline;
 pub mod pretty_print;
-
-use std::collections::{BTreeMap, HashMap};
+pub mod proximity;
+pub mod random;
+pub mod repl;
+pub mod report;
+pub mod saga;
+pub mod schedule;
+pub mod spatial;
+pub mod travel;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::sync::Arc;
 use specs::{prelude::*, Component, VecStorage};
 
 use action::*;
+use backtrace::Backtrace;
+use boarding::Parent;
 use time::*;
+use debug::Debugger;
+use fault::FaultPolicy;
+use proximity::Watch;
+use random::Rng;
+use spatial::{SpatialIndex, Tags};
+use history::*;
+use knowledge::{Fact, Knowledge};
 use task::*;
+use travel::DriveProfile;
 
 use vek::Vec3;
 
@@ -22,10 +57,46 @@ pub struct Workspace {
     methods: HashMap<Arc<str>, Arc<Method>>,
     supervisor: Entity,
     task_counter: u64,
+    queue: BinaryHeap<Reverse<(SortToken, Entity)>>,
+    listeners: HashMap<Signal, HashSet<Entity>>,
+    history: History,
+    echo: bool,
+
+    /// Every script performed from outside, for static checks
+    scripts: Vec<Arc<[Action]>>,
+
+    /// Whether the simulation halted because nothing was left to run
+    ran_dry: bool,
+
+    seed: u64,
+    rng: Rng,
+
+    /// Set while the host evaluates something, so draws leave no trace
+    aside: bool,
+
+    /// Notes on the action being performed, copied onto its events
+    note: Option<Arc<Annotation>>,
+
+    /// Where everyone is at `now`, if anyone has asked since it last changed
+    spatial: Option<SpatialIndex>,
+
+    /// Fibers waiting for two actors to draw near
+    watches: Vec<Watch>,
+
+    /// What a failing fiber takes down with it
+    fault_policy: FaultPolicy,
+
+    /// Who hears `#fault(actor)` when a failure is isolated
+    fault_supervisor: Option<Entity>,
+
+    // Boxed because fibers move back and forth between here and the agendas
+    #[allow(clippy::vec_box)]
+    active: Vec<Box<Fiber>>,
+    debugger: Debugger,
 }
 
 /// Trajectory in space, as a function from time to position
-#[derive(Copy, Clone, Component)]
+#[derive(Copy, Clone, Debug, PartialEq, Component)]
 #[storage(VecStorage)]
 pub enum Trajectory {
     Fixed {
@@ -39,12 +110,15 @@ pub enum Trajectory {
         accel: Vec3<f64>,
     },
 
-    // TODO: Orbit, once parent/child entities are implemented
-
-    // TODO: ThrustBrake, with accel in light-seconds per second per second
-    // Total travel time is 2.0 * (distance / accel).sqrt(), for const accel
+    /// Straight-line trip under a drive profile, ending at rest
+    Transfer {
+        start_place: Position,
+        start_time: Instant,
+        end_place: Position,
+        profile: DriveProfile,
+    },
 
-    // TODO: ThrustCoast, maybe
+    // TODO: Orbit, once parent/child entities are implemented
 }
 
 #[derive(Default, Component)]
@@ -52,24 +126,32 @@ pub enum Trajectory {
 pub struct Agenda {
     next: Option<QueuedTask>,
     listening: HashMap<Signal, Waiting>,
+
+    /// Set going by `every`, and queued alongside `next` without cancelling it
+    recurring: Vec<Recurrence>,
+
+    /// Waiting for actors to meet who, on their present courses, never will.
+    /// Like `next`, it is cancelled by whatever task comes after it.
+    watching: Option<Waiting>,
 }
 
 /// Current position in space, measured in light-seconds
-#[derive(Copy, Clone, Component)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component)]
 #[storage(VecStorage)]
-pub struct Position(vek::Vec3<f64>);
+pub struct Position(pub vek::Vec3<f64>);
 
 #[derive(Copy, Clone, Component)]
 #[storage(VecStorage)]
-pub struct CreationDate(Instant);
+pub struct CreationDate(pub Instant);
 
 #[derive(Clone, Component)]
 #[storage(VecStorage)]
-pub struct Name(Arc<str>);
+pub struct Name(pub Arc<str>);
 
-#[derive(Copy, Clone, Component)]
+#[derive(Copy, Clone, Default, Component)]
 #[storage(VecStorage)]
 pub enum Liveness {
+    #[default]
     Alive,
     Dead,
 }
@@ -82,6 +164,15 @@ pub enum Error {
     NoSuchField { name: Arc<str>, on_value: Value },
     NoSuchMethod { name: Arc<str>, },
     ArgListMismatch { name: Arc<str>, wanted: usize, got: usize, },
+    NotAnActor { value: Value, },
+    NoIntercept { target: Value, },
+    BadArgument { name: Arc<str>, value: Value, },
+    Aboard { name: Arc<str>, },
+    CannotBoard { name: Arc<str>, container: Arc<str>, },
+    OutOfReach { name: Arc<str>, container: Arc<str>, distance: f64, },
+
+    /// Another error, and which fiber was doing what when it happened
+    InFiber { error: Box<Error>, backtrace: Box<Backtrace>, },
 }
 
 pub type Result<T, E=Error> = std::result::Result<T, E>;
@@ -96,6 +187,9 @@ impl Workspace {
         world.register::<CreationDate>();
         world.register::<Liveness>();
         world.register::<Name>();
+        world.register::<Knowledge>();
+        world.register::<Parent>();
+        world.register::<Tags>();
 
         let init_name: Arc<str> = "Everything".into();
 
@@ -117,11 +211,49 @@ impl Workspace {
             supervisor,
             has_halted: false,
             task_counter: 0,
+            queue: BinaryHeap::new(),
+            listeners: HashMap::new(),
+            history: History::default(),
+            echo: true,
+            scripts: Vec::new(),
+            ran_dry: false,
+            seed: 0,
+            rng: Rng::seeded(0),
+            aside: false,
+            note: None,
+            spatial: None,
+            watches: Vec::new(),
+            fault_policy: FaultPolicy::default(),
+            fault_supervisor: None,
+            active: Vec::new(),
+            debugger: Debugger::default(),
         }
     }
 
+    /// Run until a `halt`. An `every` with no `until` keeps the queue busy
+    /// forever, so a story using one must `halt` to end.
     pub fn simulate(&mut self) -> Result<()> {
-        while !self.has_halted {
+        while !self.has_halted && !self.is_paused() {
+            self.update()?;
+        }
+
+        Ok(())
+    }
+
+    /// Run until halted, or until `limit`. If nothing else is due by then,
+    /// the clock moves on to `limit` without halting.
+    pub fn simulate_until(&mut self, limit: Instant) -> Result<()> {
+        while !self.has_halted && !self.is_paused() {
+            let due = !self.active.is_empty() || self.next_eta().is_some_and(|eta| eta <= limit);
+
+            if !due {
+                if limit > self.now {
+                    self.advance_clock(limit);
+                }
+
+                break;
+            }
+
             self.update()?;
         }
 
@@ -132,176 +264,599 @@ impl Workspace {
         self.has_halted
     }
 
+    /// Whether to echo each action to stderr as it is performed
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// The actor, named Everything, that performs scripts from outside
+    pub fn supervisor(&self) -> Entity {
+        self.supervisor
+    }
+
+    /// Evaluate an expression as the supervisor, at the current instant
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Value> {
+        let fiber = Fiber::new(0, self.supervisor, Vec::new().into());
+        self.eval_aside(&fiber, expr)
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn name_of(&self, id: Entity) -> Option<Arc<str>> {
+        self.world.read_component::<Name>().get(id).map(|name| name.0.clone())
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Entity> {
+        self.globals.get(name).cloned()
+    }
+
+    /// Every named actor, sorted by name
+    pub fn globals(&self) -> Vec<(Arc<str>, Entity)> {
+        let mut globals = self.globals.iter()
+            .map(|(name, &id)| (name.clone(), id))
+            .collect::<Vec<_>>();
+        globals.sort();
+        globals
+    }
+
+    pub fn position_of(&self, id: Entity) -> Result<Position> {
+        self.get_position(id)
+    }
+
+    pub fn creation_date_of(&self, id: Entity) -> Option<Instant> {
+        self.world.read_component::<CreationDate>().get(id).map(|date| date.0)
+    }
+
+    pub fn liveness_of(&self, id: Entity) -> Liveness {
+        self.world.read_component::<Liveness>().get(id).cloned().unwrap_or_default()
+    }
+
+    /// When the actor's next queued task is due, if it has one
+    pub fn next_task_of(&self, id: Entity) -> Option<Instant> {
+        self.world.read_component::<Agenda>().get(id)
+            .and_then(|agenda| agenda.next.as_ref())
+            .map(|task| task.token.eta)
+    }
+
+    /// Signals the actor has fibers waiting on
+    pub fn listening_for(&self, id: Entity) -> Vec<Signal> {
+        self.world.read_component::<Agenda>().get(id)
+            .map(|agenda| agenda.listening.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
     pub fn perform(&mut self, script: Arc<[Action]>) -> Result<()> {
-        self.run(Fiber::new(self.supervisor, script).into())
+        self.scripts.push(script.clone());
+        let fiber = self.new_fiber(self.supervisor, script);
+        self.run(fiber)
     }
 
-    fn run(&mut self, mut fiber: Box<Fiber>) -> Result<()> {
-        while let Some(action) = fiber.fetch() {
-            eprintln!("{:<8.0}: {}", f64::from(self.now), action);
+    /// Like `perform`, but starting from the given locals. If the script runs
+    /// to completion without suspending, its final locals are written back.
+    pub fn perform_with(&mut self, script: Arc<[Action]>, locals: &mut HashMap<Arc<str>, Value>) -> Result<()> {
+        self.scripts.push(script.clone());
+        let mut fiber = self.new_fiber(self.supervisor, script);
+        fiber.frame_mut().unwrap().locals = locals.clone();
 
-            match action {
-                Action::Halt => self.has_halted = true,
+        if let Some(fiber) = self.run_fiber(fiber)? {
+            if let Some(frame) = fiber.frame() {
+                *locals = frame.locals.clone();
+            }
+        }
 
-                Action::Trace { expr } => {
-                    eprintln!("\t> {} = {}", &expr, self.eval_expr(&fiber, &expr)?);
-                },
+        Ok(())
+    }
 
-                Action::Spawn { name } => {
-                    let position = self.get_position(fiber.me)?;
+    fn run(&mut self, fiber: Box<Fiber>) -> Result<()> {
+        self.run_fiber(fiber).map(|_finished| ())
+    }
 
-                    let id = self.world.create_entity()
-                        .with(Name(name.as_ref().into()))
-                        .with(CreationDate(self.now))
-                        .with(Agenda::default())
-                        .with(Trajectory::Fixed { value: position })
-                        .build();
+    /// Returns the fiber if it ran out of actions, or None if it suspended
+    /// or the debugger paused it
+    fn run_fiber(&mut self, fiber: Box<Fiber>) -> Result<Option<Box<Fiber>>> {
+        let base = self.active.len();
+        self.active.push(fiber);
+        self.drive(base)
+    }
 
-                    self.globals.insert(name.clone(), id);
+    /// Run the active fibers until the one at `base` is done with, or until
+    /// the debugger pauses. Fibers started by `as ... do` sit above their
+    /// parents on the active stack, and run first.
+    fn drive(&mut self, base: usize) -> Result<Option<Box<Fiber>>> {
+        while self.active.len() > base {
+            let fiber = self.active.pop().unwrap();
+
+            if let Some(reason) = self.check_pause(&fiber) {
+                self.active.push(fiber);
+                self.pause(reason);
+                return Ok(None);
+            }
+
+            let step = match self.execute(fiber) {
+                Ok(step) => step,
+                Err(err) => {
+                    if self.fault_policy == FaultPolicy::Abort {
+                        self.active.truncate(base);
+                        return Err(err);
+                    }
+
+                    self.isolate(err)?;
+                    Step::Suspended
                 },
+            };
 
-                Action::AsActor { name, script } => {
-                    let me = *self.globals.get(name.as_ref())
-                        .ok_or_else(|| Error::NoSuchGlobal { name: name.clone() })?;
+            match step {
+                Step::Continue(fiber) => self.active.push(fiber),
 
-                    let locals = fiber.frame().unwrap().locals.clone();
+                Step::Enter { parent, child } => {
+                    self.active.push(parent);
+                    self.active.push(child);
+                },
 
-                    let mut fiber = Box::new(Fiber::new(me, script));
-                    fiber.frame_mut().unwrap().locals = locals;
-                    self.run(fiber)?;
-                    // Execution resumes where it left off
+                Step::Suspended => (),
+
+                Step::Finished(fiber) => if self.active.len() == base {
+                    return Ok(Some(fiber));
                 },
+            }
+        }
 
-                Action::SetAccel { value } => {
-                    let start_time = self.now;
-                    let start_place = self.get_position(fiber.me)?;
+        Ok(None)
+    }
 
-                    let mut storage = self.world.write_component::<Trajectory>();
+    /// Perform the fiber's next action. Errors come back with a backtrace.
+    fn execute(&mut self, mut fiber: Box<Fiber>) -> Result<Step> {
+        self.note = None;
 
-                    let trajectory = storage.get_mut(fiber.me)
-                        .ok_or_else(|| Error::CouldNotWrite { component: "Trajectory" })?;
+        if let Some((signal, transmission)) = fiber.woken_by.take() {
+            let reception = self.record(&fiber, EventKind::Received { signal: signal.clone(), transmission });
+            self.learn_reception(fiber.me, signal, transmission, reception);
+        }
 
-                    let start_velocity = trajectory.velocity_at(self.now);
+        let action = match fiber.fetch() {
+            Some(Action::Annotated { action, annotation }) => {
+                self.note = Some(annotation);
+                action.unannotated().clone()
+            },
 
-                    if start_velocity.magnitude_squared() == 0.0 && value.magnitude_squared() == 0.0 {
-                        *trajectory = Trajectory::Fixed { value: start_place };
-                    } else {
-                        *trajectory = Trajectory::Linear {
-                            start_place,
-                            start_time,
-                            start_velocity,
-                            accel: value,
-                        }
-                    };
-                },
+            Some(action) => action,
+            None => return Ok(Step::Finished(fiber)),
+        };
 
-                Action::Wait { interval } => {
-                    let guid = self.make_guid();
-                    let token = SortToken { guid, eta: self.now + interval };
+        if self.echo {
+            eprintln!("{:<8.0}: {}", f64::from(self.now), action);
+        }
 
-                    let me = fiber.me;
-                    self.world.write_component::<Agenda>()
-                        .get_mut(me)
-                        .ok_or(Error::CouldNotWrite { component: "Agenda" })?
-                        .next = Some(QueuedTask { fiber, token });
+        match &action {
+            // These record their own, more detailed events
+            Action::Halt | Action::Trace { .. } | Action::Spawn { .. } | Action::Transmit { .. } => (),
 
-                    break;
-                },
+            // Only there to jump over an `else` block
+            Action::Skip { .. } => (),
 
-                Action::ListenFor { head, args } => {
-                    let guid = self.make_guid();
+            _ => {
+                self.record(&fiber, EventKind::Performed { action: action.clone() });
+            },
+        }
 
-                    let body = args.iter().map(|arg| {
-                        self.eval_expr(&fiber, arg)
-                    }).collect::<Result<Arc<[Value]>>>()?;
+        let outcome = match self.perform_action(&mut fiber, action) {
+            Ok(outcome) => outcome,
+            Err(err) => return Err(self.fail_at(&fiber, err)),
+        };
+
+        // Past here the fiber is handed on, and only who it was is left
+        let (me, id) = (fiber.me, fiber.id);
+
+        let handed_on = match outcome {
+            Outcome::Continue => return Ok(Step::Continue(fiber)),
+            Outcome::Enter(child) => return Ok(Step::Enter { parent: fiber, child }),
+            Outcome::Resume { at } => self.schedule(fiber, at).map(|_token| ()),
+            Outcome::Watch { subject, target, distance } => self.watch(fiber, subject, target, distance),
+            Outcome::Listen(signal) => self.listen(fiber, signal),
+        };
+
+        match handed_on {
+            Ok(()) => Ok(Step::Suspended),
+            Err(err) => Err(self.fail_in(me, id, Vec::new(), err)),
+        }
+    }
 
-                    let signal = Signal { head, body };
+    /// Park the fiber until it hears `signal`
+    fn listen(&mut self, fiber: Box<Fiber>, signal: Signal) -> Result<()> {
+        let guid = self.make_guid();
 
-                    self.world.write_component::<Agenda>().get_mut(fiber.me)
-                        .ok_or(Error::CouldNotWrite { component: "Agenda" })?
-                        .listening.insert(signal, Waiting { guid, fiber });
+        let me = fiber.me;
+        self.world.write_component::<Agenda>().get_mut(me)
+            .ok_or(Error::CouldNotWrite { component: "Agenda" })?
+            .listening.insert(signal.clone(), Waiting { guid, fiber });
 
-                    break;
-                },
+        self.listeners.entry(signal).or_default().insert(me);
+        Ok(())
+    }
 
-                Action::Transmit { head, args } => {
-                    let body = args.iter().map(|arg| {
-                        self.eval_expr(&fiber, arg)
-                    }).collect::<Result<Arc<[Value]>>>()?;
+    /// Do what the action says, up to handing the fiber on
+    fn perform_action(&mut self, fiber: &mut Fiber, action: Action) -> Result<Outcome> {
+        match action {
+            Action::Halt => {
+                self.record(fiber, EventKind::Halted);
+                self.has_halted = true;
+            },
 
-                    let signal = Signal { head, body };
+            Action::Trace { expr } => {
+                let value = self.eval_expr(fiber, &expr)?;
 
-                    // TODO: Light cone signal delay?
-                    let mut agenda = self.world.write_component::<Agenda>();
+                if self.echo {
+                    eprintln!("\t> {} = {}", &expr, value);
+                }
 
-                    for agenda in (&mut agenda).join() {
-                        if let Some(Waiting { guid, fiber }) = agenda.listening.remove(&signal) {
-                            let eta = self.now;
-                            let token = SortToken { eta, guid };
-                            agenda.next = Some(QueuedTask { token, fiber });
-                        }
-                    }
-                },
+                self.record(fiber, EventKind::Traced { expr, value });
+            },
 
-                Action::Die => {
-                    self.world.write_component::<Liveness>().insert(fiber.me, Liveness::Dead)
-                    .map_err(|_err| Error::CouldNotWrite { component: "Liveness" })?;
-                },
+            Action::Spawn { name } => {
+                let position = self.get_position(fiber.me)?;
 
-                Action::WriteLocal { name, value } => {
-                    let value = self.eval_expr(&fiber, &value)?;
-                    fiber.frame_mut().unwrap().locals.insert(name, value);
-                },
+                let id = self.world.create_entity()
+                    .with(Name(name.as_ref().into()))
+                    .with(CreationDate(self.now))
+                    .with(Agenda::default())
+                    .with(Trajectory::Fixed { value: position })
+                    .build();
 
-                Action::DefGlobalMethod { name, body } => {
-                    self.methods.insert(name, body);
-                },
+                self.history.record_segment(id, self.now, Trajectory::Fixed { value: position });
+                self.spatial = None;
+                self.globals.insert(name.clone(), id);
+                self.record(fiber, EventKind::Spawned { name, child: id });
+            },
 
-                Action::Call { name, args } => {
-                    let method = self.methods.get(&name).cloned().ok_or_else(|| {
-                        Error::NoSuchMethod { name: name.clone() }
-                    })?;
-
-                    if method.params.len() != args.len() {
-                        return Err(Error::ArgListMismatch {
-                            name: name.clone(),
-                            wanted: method.params.len(),
-                            got: args.len(),
-                        });
-                    }
+            Action::AsActor { name, script } => {
+                let me = *self.globals.get(name.as_ref())
+                    .ok_or_else(|| Error::NoSuchGlobal { name: name.clone() })?;
 
-                    let mut locals = HashMap::new();
+                let locals = fiber.frame().unwrap().locals.clone();
 
-                    for (param, arg) in method.params.iter().zip(args.iter()) {
-                        locals.insert(param.clone(), self.eval_expr(&fiber, arg)?);
+                let mut child = self.new_fiber(me, script);
+                child.frame_mut().unwrap().locals = locals;
+
+                // The `as` event itself was the last one recorded
+                let origin = self.history.len() - 1;
+                self.history.record_fiber_origin(child.id, origin);
+
+                // Execution resumes where it left off, once the child is done
+                return Ok(Outcome::Enter(child));
+            },
+
+            Action::SetAccel { value } => {
+                self.check_not_aboard(fiber.me)?;
+
+                let start_time = self.now;
+                let start_place = self.get_position(fiber.me)?;
+
+                let start_velocity = self.world.read_component::<Trajectory>()
+                    .get(fiber.me)
+                    .ok_or(Error::CouldNotWrite { component: "Trajectory" })?
+                    .velocity_at(self.now);
+
+                let trajectory = if start_velocity.magnitude_squared() == 0.0 && value.magnitude_squared() == 0.0 {
+                    Trajectory::Fixed { value: start_place }
+                } else {
+                    Trajectory::Linear {
+                        start_place,
+                        start_time,
+                        start_velocity,
+                        accel: value,
                     }
+                };
+
+                self.set_trajectory(fiber.me, trajectory)?;
+            },
+
+            Action::Wait { interval } => {
+                return Ok(Outcome::Resume { at: self.now + interval });
+            },
+
+            Action::WaitUntil { at } => {
+                return Ok(Outcome::Resume { at: at.max(self.now) });
+            },
+
+            Action::Every { interval, from, until, script } => {
+                // The `every` event itself was the last one recorded
+                let origin = self.history.len() - 1;
+                self.start_recurrence(fiber, interval, from, until, script, origin)?;
+            },
+
+            Action::WaitNear { subject, distance, target } => {
+                let subject = self.eval_actor(fiber, &subject)?;
+                let target = self.eval_actor(fiber, &target)?;
+
+                let distance = match self.eval_expr(fiber, &distance)? {
+                    Value::Num(distance) if distance.into_inner() >= 0.0 => distance.into_inner(),
+                    other => return Err(Error::BadArgument { name: "within".into(), value: other }),
+                };
+
+                return Ok(Outcome::Watch { subject, target, distance });
+            },
+
+            Action::TravelTo { target, profile } => {
+                self.check_not_aboard(fiber.me)?;
+
+                let target = match self.eval_expr(fiber, &target)? {
+                    Value::ActorId(id) => id,
+                    other => return Err(Error::NotAnActor { value: other }),
+                };
+
+                let start_place = self.get_position(fiber.me)?;
+
+                let course = self.world.read_component::<Trajectory>()
+                    .get(target).cloned().unwrap_or_default();
+
+                let (eta, trajectory) = travel::plan_intercept(start_place, self.now, &course, profile)
+                    .ok_or(Error::NoIntercept { target: Value::ActorId(target) })?;
+
+                self.set_trajectory(fiber.me, trajectory)?;
+                return Ok(Outcome::Resume { at: eta });
+            },
+
+            Action::ListenFor { head, args } => {
+                let body = args.iter().map(|arg| {
+                    self.eval_expr(fiber, arg)
+                }).collect::<Result<Arc<[Value]>>>()?;
+
+                return Ok(Outcome::Listen(Signal { head, body }));
+            },
+
+            Action::Transmit { head, args } => {
+                let body = args.iter().map(|arg| {
+                    self.eval_expr(fiber, arg)
+                }).collect::<Result<Arc<[Value]>>>()?;
+
+                let signal = Signal { head, body };
+                let transmission = self.record(fiber, EventKind::Transmitted { signal: signal.clone() });
+                self.learn(fiber.me, Fact::Signal { signal: signal.clone() }, transmission);
+
+                // TODO: Light cone signal delay?
+                for id in self.listeners.remove(&signal).unwrap_or_default() {
+                    self.wake(id, &signal, transmission);
+                }
+            },
+
+            Action::Die => {
+                self.kill(fiber.me)?;
+            },
+
+            Action::Tag { tag } => {
+                self.add_tag(fiber.me, tag)?;
+            },
+
+            Action::Board { container } => {
+                match self.eval_expr(fiber, &container)? {
+                    Value::ActorId(id) => self.board(fiber.me, id)?,
+                    other => return Err(Error::NotAnActor { value: other }),
+                }
+            },
+
+            Action::Disembark => {
+                self.disembark(fiber.me)?;
+            },
+
+            Action::WriteLocal { name, value } => {
+                let value = self.eval_expr(fiber, &value)?;
+                fiber.frame_mut().unwrap().locals.insert(name, value);
+            },
+
+            Action::DefGlobalMethod { name, body } => {
+                self.methods.insert(name, body);
+            },
+
+            Action::Call { name, args } => {
+                let method = self.methods.get(&name).cloned().ok_or_else(|| {
+                    Error::NoSuchMethod { name: name.clone() }
+                })?;
 
-                    fiber.stack.push(StackFrame {
-                        pc: 0,
-                        locals,
-                        script: method.script.clone(),
+                if method.params.len() != args.len() {
+                    return Err(Error::ArgListMismatch {
+                        name: name.clone(),
+                        wanted: method.params.len(),
+                        got: args.len(),
                     });
-                },
+                }
 
-                Action::Return => {
-                    fiber.stack.pop();
-                },
+                let mut locals = HashMap::new();
 
-                //_ => eprintln!("Not yet implemented: {:?}", action),
-            }
+                for (param, arg) in method.params.iter().zip(args.iter()) {
+                    locals.insert(param.clone(), self.eval_expr(fiber, arg)?);
+                }
+
+                fiber.stack.push(StackFrame {
+                    pc: 0,
+                    locals,
+                    script: method.script.clone(),
+                    method: Some(name),
+                });
+            },
 
+            Action::Return => {
+                fiber.stack.pop();
+            },
+
+            Action::If { condition, skip } => {
+                if !self.check_condition(fiber, &condition)? {
+                    fiber.frame_mut().unwrap().pc += skip;
+                }
+            },
+
+            Action::Skip { skip } => {
+                fiber.frame_mut().unwrap().pc += skip;
+            },
+
+            Action::Annotated { .. } => unreachable!("annotations are peeled off above"),
+
+            //_ => eprintln!("Not yet implemented: {:?}", action),
         }
 
-        Ok(())
+        Ok(Outcome::Continue)
     }
 
     pub fn update(&mut self) -> Result<()> {
-        let (time, fiber) = self.find_next_task();
+        if !self.active.is_empty() {
+            // Finish the task the debugger interrupted
+            self.unpause();
+            return self.drive(0).map(|_finished| ());
+        }
+
+        if let Err(err) = self.begin_task() {
+            if self.fault_policy == FaultPolicy::Abort {
+                return Err(err);
+            }
+
+            return self.isolate(err);
+        }
+
+        self.drive(0).map(|_finished| ())
+    }
+
+    /// Advance the clock to the next queued task and make it active
+    fn begin_task(&mut self) -> Result<()> {
+        let (time, fiber) = self.find_next_task()?;
 
+        self.advance_clock(time);
+        self.active.push(fiber);
+        Ok(())
+    }
+
+    /// Move the clock on, forgetting positions worked out for the old time
+    fn advance_clock(&mut self, time: Instant) {
         assert!(time >= self.now, "Time went backwards");
         self.now = time;
         self.world.write_component::<Position>().clear();
-        self.run(fiber)
+        self.spatial = None;
+    }
+
+    fn set_trajectory(&mut self, id: Entity, trajectory: Trajectory) -> Result<()> {
+        self.world.write_component::<Trajectory>().insert(id, trajectory)
+            .map_err(|_err| Error::CouldNotWrite { component: "Trajectory" })?;
+
+        self.history.record_segment(id, self.now, trajectory);
+        self.spatial = None;
+        self.rewatch(id)?;
+
+        // Whoever is aboard goes along
+        for passenger in self.passengers_of(id) {
+            self.set_trajectory(passenger, trajectory)?;
+        }
+
+        Ok(())
+    }
+
+    fn schedule(&mut self, fiber: Box<Fiber>, eta: Instant) -> Result<SortToken> {
+        let guid = self.make_guid();
+        let token = SortToken { guid, eta };
+
+        let me = fiber.me;
+        self.world.write_component::<Agenda>()
+            .get_mut(me)
+            .ok_or(Error::CouldNotWrite { component: "Agenda" })?
+            .queue(QueuedTask { fiber, token });
+
+        self.enqueue(token, me);
+        Ok(token)
+    }
+
+    /// Put a task on the global queue. There is work to do again, so the
+    /// simulation can no longer be said to have run dry.
+    fn enqueue(&mut self, token: SortToken, id: Entity) {
+        self.queue.push(Reverse((token, id)));
+        self.ran_dry = false;
+    }
+
+    /// When the next queued task is due, if there is one
+    pub fn next_eta(&mut self) -> Option<Instant> {
+        let agenda = self.world.read_component::<Agenda>();
+
+        while let Some(&Reverse((token, id))) = self.queue.peek() {
+            if agenda.get(id).is_some_and(|agenda| agenda.holds(token)) {
+                return Some(token.eta);
+            }
+
+            self.queue.pop();
+        }
+
+        None
+    }
+
+    fn new_fiber(&mut self, me: Entity, script: Arc<[Action]>) -> Box<Fiber> {
+        let id = self.make_guid();
+        Fiber::new(id, me, script).into()
+    }
+
+    fn record(&mut self, fiber: &Fiber, kind: EventKind) -> EventId {
+        self.history.record(Event {
+            time: self.now,
+            actor: fiber.me,
+            fiber: fiber.id,
+            kind,
+            annotation: self.note.clone(),
+        })
+    }
+
+    /// Queue the actor's fiber listening for `signal`, if it has one, to
+    /// receive it now
+    fn wake(&mut self, id: Entity, signal: &Signal, transmission: EventId) {
+        let token = {
+            let mut agenda = self.world.write_component::<Agenda>();
+            let agenda = match agenda.get_mut(id) {
+                Some(agenda) => agenda,
+                None => return,
+            };
+
+            let Waiting { guid, mut fiber } = match agenda.listening.remove(signal) {
+                Some(waiting) => waiting,
+                None => return,
+            };
+
+            fiber.woken_by = Some((signal.clone(), transmission));
+            let token = SortToken { eta: self.now, guid };
+            agenda.queue(QueuedTask { token, fiber });
+            token
+        };
+
+        self.enqueue(token, id);
+    }
+
+    /// Mark the actor dead, with nothing left to do: its queued tasks,
+    /// listeners, watches and recurring schedules lapse with it, and anyone
+    /// aboard is set down. A fiber already running carries on to its end.
+    pub(crate) fn kill(&mut self, actor: Entity) -> Result<()> {
+        self.world.write_component::<Liveness>().insert(actor, Liveness::Dead)
+            .map_err(|_err| Error::CouldNotWrite { component: "Liveness" })?;
+
+        self.stop_listening(actor);
+
+        if let Some(agenda) = self.world.write_component::<Agenda>().get_mut(actor) {
+            *agenda = Agenda::default();
+        }
+
+        self.forget_watches(actor);
+        self.set_down_passengers(actor)?;
+        self.spatial = None;
+        Ok(())
+    }
+
+    /// Drop every fiber the actor has waiting on a signal
+    fn stop_listening(&mut self, id: Entity) {
+        if let Some(agenda) = self.world.write_component::<Agenda>().get_mut(id) {
+            agenda.listening.clear();
+        }
+
+        self.listeners.retain(|_, ids| {
+            ids.remove(&id);
+            !ids.is_empty()
+        });
     }
 
     fn make_guid(&mut self) -> u64 {
@@ -315,13 +870,28 @@ impl Workspace {
             Expr::Myself => Value::ActorId(fiber.me),
 
             Expr::Field { subject, field_name } => {
-                match self.eval_expr(fiber, &subject)? {
-                    Value::ActorId(id) => match field_name.as_ref() {
-                        "position" => self.get_position(id)?.into(),
+                match self.eval_expr(fiber, subject)? {
+                    Value::ActorId(id) => {
+                        let no_such_field = || Error::NoSuchField {
+                            name: field_name.clone(),
+                            on_value: Value::ActorId(id),
+                        };
+
+                        match field_name.as_ref() {
+                            "position" => self.get_position(id)?.into(),
+                            "parent" => Value::ActorId(self.parent_of(id).ok_or_else(no_such_field)?),
+                            _ => Err(no_such_field())?,
+                        }
+                    },
+
+                    Value::List(values) => match field_name.as_ref() {
+                        "len" => Value::Num((values.len() as f64).into()),
+                        "first" if !values.is_empty() => values[0].clone(),
+                        "last" if !values.is_empty() => values[values.len() - 1].clone(),
 
                         _ => Err(Error::NoSuchField {
                             name: field_name.clone(),
-                            on_value: Value::ActorId(id),
+                            on_value: Value::List(values),
                         })?,
                     },
 
@@ -342,7 +912,7 @@ impl Workspace {
             },
 
             Expr::Var { name } => {
-                fiber.frame().unwrap().locals.get(name).map(Clone::clone).or_else(|| {
+                fiber.frame().unwrap().locals.get(name).cloned().or_else(|| {
                     self.globals.get(name).map(|&id| Value::ActorId(id))
                 }).ok_or(Error::NoSuchGlobal { name: name.clone() })?
             },
@@ -350,9 +920,44 @@ impl Workspace {
             Expr::NumConst { value } => {
                 Value::Num((*value).into())
             },
+
+            Expr::Builtin { func, args } => {
+                let args = args.iter().map(|arg| {
+                    self.eval_expr(fiber, arg)
+                }).collect::<Result<Vec<Value>>>()?;
+
+                self.eval_builtin(*func, &args)?
+            },
+
+            Expr::Random { dist, args } => {
+                let args = args.iter().map(|arg| {
+                    self.eval_expr(fiber, arg)
+                }).collect::<Result<Vec<Value>>>()?;
+
+                self.draw(fiber, *dist, args)?
+            },
+
+            Expr::Within { center, radius, tag } => {
+                let center = self.eval_expr(fiber, center)?;
+                let radius = self.eval_expr(fiber, radius)?;
+                self.eval_within(center, radius, tag.as_deref())?
+            },
+
+            Expr::Nearest { center, count, tag } => {
+                let center = self.eval_expr(fiber, center)?;
+                let count = self.eval_expr(fiber, count)?;
+                self.eval_nearest(center, count, tag.as_deref())?
+            },
         })
     }
 
+    fn eval_actor(&mut self, fiber: &Fiber, expr: &Expr) -> Result<Entity> {
+        match self.eval_expr(fiber, expr)? {
+            Value::ActorId(id) => Ok(id),
+            other => Err(Error::NotAnActor { value: other }),
+        }
+    }
+
     fn get_position(&self, id: Entity) -> Result<Position> {
         let mut positions = self.world.write_component::<Position>();
         let trajectories = self.world.read_component::<Trajectory>();
@@ -369,41 +974,70 @@ impl Workspace {
         Ok(position)
     }
 
-    // When tasks were queued globally, we just called pop() on a BinaryHeap.
-    // In order to implement a cancellation policy, tasks are now queued on the
-    // actors that will perform them. Finding the task in question thus becomes
-    // a little bit more complicated: We have to inspect every actor.
-    fn find_next_task(&mut self) -> (Instant, Box<Fiber>) {
-        let entities = self.world.entities();
-        let mut agenda = self.world.write_component::<Agenda>();
-
-        // Expanded from a .filter_map().min_by_key() iterator
-        // because the borrow checker got a little confused
-        let mut next: Option<(Entity, SortToken)> = None;
-        for (id, agenda) in (&entities, &agenda).join() {
-            let token = match agenda.next.clone() {
-                Some(task) => task.token,
+    // Tasks are queued on the actors that will perform them, so that queueing
+    // a new task for an actor cancels whatever it was going to do next. The
+    // global heap only says where to look: an entry whose token no longer
+    // matches its actor's agenda was cancelled, and is discarded when popped.
+    fn find_next_task(&mut self) -> Result<(Instant, Box<Fiber>)> {
+        while let Some(Reverse((token, id))) = self.queue.pop() {
+            let mut agendas = self.world.write_component::<Agenda>();
+
+            let agenda = match agendas.get_mut(id) {
+                Some(agenda) => agenda,
                 None => continue,
             };
 
-            if let Some((_, prev_token)) = next.as_ref() {
-                if prev_token < &token {
+            if agenda.next.as_ref().map(|task| task.token) == Some(token) {
+                return Ok(agenda.next.take().unwrap().into());
+            }
+
+            if let Some(index) = agenda.recurring.iter().position(|recurrence| recurrence.token == token) {
+                let recurrence = agenda.recurring.remove(index);
+                drop(agendas);
+
+                // The dead keep no appointments
+                if matches!(self.liveness_of(id), Liveness::Dead) {
                     continue;
                 }
-            }
 
-            next = Some((id, token));
+                return Ok((token.eta, self.recur(id, recurrence)?));
+            }
         }
 
-        if let Some((id, _token)) = next {
-            let task = agenda.get_mut(id).unwrap().next.take().unwrap();
-            return task.into();
-        } else {
-            let eta = self.now + Interval::one();
-            let script = vec![Action::Halt].into();
-            let fiber = Fiber::new(self.supervisor, script).into();
-            (eta, fiber)
-        }
+        // Nothing left to run, so anyone still listening is stuck for good
+        self.ran_dry = true;
+
+        let eta = self.now + Interval::one();
+        let script = vec![Action::Halt].into();
+        let fiber = self.new_fiber(self.supervisor, script);
+        Ok((eta, fiber))
+    }
+}
+
+impl Agenda {
+    /// Make this the actor's next task, cancelling the one before
+    fn queue(&mut self, task: QueuedTask) {
+        self.next = Some(task);
+        self.watching = None;
+    }
+
+    /// Set the fiber aside until the watch on it is worked out again,
+    /// cancelling the task before
+    pub(crate) fn park(&mut self, waiting: Waiting) {
+        self.next = None;
+        self.watching = Some(waiting);
+    }
+
+    /// Whether the queue entry with this token is still wanted
+    fn holds(&self, token: SortToken) -> bool {
+        self.next.as_ref().is_some_and(|task| task.token == token)
+            || self.recurring.iter().any(|recurrence| recurrence.token == token)
+    }
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace::new()
     }
 }
 
@@ -419,8 +1053,11 @@ impl Trajectory {
                 ..
             } => {
                 let dt = start_time.delta(time);
-                let dv = accel * f64::from(dt).powi(2) * 0.5;
-                start_velocity + dv
+                start_velocity + accel * f64::from(dt)
+            },
+
+            Trajectory::Transfer { start_place, start_time, end_place, profile } => {
+                Trajectory::transfer_velocity(start_place, start_time, end_place, profile, time)
             },
         }
     }
@@ -432,11 +1069,15 @@ impl Trajectory {
             Trajectory::Linear {
                 start_place,
                 start_time,
-                ..
+                start_velocity,
+                accel,
             } => {
-                let dt = start_time.delta(time);
-                let velocity = self.velocity_at(time);
-                start_place.offset(velocity * f64::from(dt))
+                let dt = f64::from(start_time.delta(time));
+                start_place.offset(start_velocity * dt + accel * dt.powi(2) * 0.5)
+            },
+
+            Trajectory::Transfer { start_place, start_time, end_place, profile } => {
+                Trajectory::transfer_position(start_place, start_time, end_place, profile, time)
             },
         }
     }
@@ -450,14 +1091,8 @@ impl Default for Trajectory {
     }
 }
 
-impl Default for Position {
-    fn default() -> Self {
-        Position(Vec3::default())
-    }
-}
-
 impl Position {
-    fn offset(self, delta: Vec3<f64>) -> Self {
+    pub(crate) fn offset(self, delta: Vec3<f64>) -> Self {
         Position(self.0 + delta)
     }
 }
@@ -473,9 +1108,3 @@ impl From<Position> for Value {
         })
     }
 }
-
-impl Default for Liveness {
-    fn default() -> Self {
-        Liveness::Alive
-    }
-}
@@ -0,0 +1,419 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+
+use histrion::Workspace;
+use histrion::causality;
+use histrion::chronicle;
+use histrion::continuity;
+use histrion::deadlock;
+use histrion::ensemble::{self, Ensemble};
+use histrion::export::{self, SampleFormat, Sampling};
+use histrion::fault::FaultPolicy;
+use histrion::import::Loader;
+use histrion::map::{self, MapSpan, MapView, Plane};
+use histrion::repl::{self, Repl};
+use histrion::report::{self, LogFormat};
+use histrion::saga;
+use histrion::timeline::{self, TimelineStyle};
+use histrion::time::{Instant, Interval};
+
+const USAGE: &str = "\
+usage: histrion [options] <saga>...
+       histrion --interactive [<saga>...]
+
+options:
+    -f, --format <text|json>   event log format (default: text)
+    -o, --output <file>        write to a file instead of stdout
+    -u, --until <duration>     stop simulating after this long, e.g. 2y
+    -s, --seed <number>        seed for random draws (default: 0)
+    -n, --runs <number>        run the sagas this many times with successive
+                               seeds, and summarize the outcomes
+        --threads <number>     threads to spread runs over (default: all cores)
+    -t, --traces               print only trace output, not the event log
+        --tag <tag>            log only events from actions tagged #tag
+        --outline              print the story told by annotations, by chapter
+        --timeline <file>      also draw a per-actor timeline as SVG
+        --causality <file>     also write the causal graph as Graphviz DOT
+        --chronicle <file>     also tell the story day by day as Markdown
+        --trajectories <file>  also sample every trajectory, as .csv or .json
+        --step <duration>      time between trajectory or trail samples (default: 1/100 of the run)
+        --segments <file>      also write the exact trajectory segments as JSON
+        --map <file>           also draw a top-down map of everyone as SVG
+        --map-at <time>        show the map at this time (default: the end)
+        --map-plane <plane>    plane to look down onto: xy, xz or yz (default: xy)
+        --map-trail            show paths across the run instead of one moment
+        --rings <actor>        draw light-delay rings around this actor
+        --on-fault <policy>    when an action fails: abort, kill-fiber or
+                               kill-actor (default: abort)
+        --notify-faults        have Everything hear #fault(actor) for each
+                               failure that doesn't abort the run
+    -c, --check                report continuity errors to stderr after the run
+    -v, --verbose              echo each action to stderr as it runs
+    -i, --interactive          load the sagas, then start a REPL
+    -h, --help                 show this message
+";
+
+struct Options {
+    format: LogFormat,
+    output: Option<String>,
+    timeline: Option<String>,
+    causality: Option<String>,
+    chronicle: Option<String>,
+    trajectories: Option<String>,
+    step: Option<Interval>,
+    segments: Option<String>,
+    map: Option<String>,
+    map_at: Option<Instant>,
+    map_plane: Plane,
+    map_trail: bool,
+    rings: Option<String>,
+    until: Option<Instant>,
+    seed: u64,
+    runs: Option<usize>,
+    threads: Option<usize>,
+    traces_only: bool,
+    tag: Option<String>,
+    outline: bool,
+    on_fault: FaultPolicy,
+    notify_faults: bool,
+    check: bool,
+    verbose: bool,
+    interactive: bool,
+    sagas: Vec<String>,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("histrion: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        },
+    };
+
+    let mut loader = Loader::new();
+
+    let scripts = options.sagas.iter().map(|path| {
+        let script = loader.load(Path::new(path)).unwrap_or_else(|err| {
+            eprintln!("histrion: {}", err);
+            process::exit(2);
+        });
+
+        script.into_inner()
+    }).collect::<Vec<_>>();
+
+    if let Some(runs) = options.runs {
+        let mut ensemble = Ensemble {
+            runs,
+            first_seed: options.seed,
+            until: options.until,
+            ..Ensemble::default()
+        };
+
+        if let Some(threads) = options.threads {
+            ensemble.threads = threads;
+        }
+
+        let report = ensemble.run(&scripts);
+        if let Err(err) = write_ensemble(&report, &options) {
+            eprintln!("histrion: cannot write output: {}", err);
+            process::exit(2);
+        }
+
+        return;
+    }
+
+    let mut workspace = Workspace::with_seed(options.seed);
+    workspace.set_echo(options.verbose);
+    workspace.set_fault_policy(options.on_fault);
+
+    if options.notify_faults {
+        workspace.set_fault_supervisor(Some(workspace.supervisor()));
+    }
+
+    let mut outcome = Ok(());
+
+    for script in scripts {
+        outcome = workspace.perform(script);
+        if outcome.is_err() {
+            break;
+        }
+    }
+
+    if options.interactive {
+        if let Err(err) = outcome {
+            eprintln!("histrion: error at {}sec: {}", f64::from(workspace.now()), err);
+        }
+
+        let mut repl = Repl::with_loader(workspace, loader);
+        let stdin = io::stdin();
+        let result = repl::run(&mut repl, &mut stdin.lock(), &mut io::stdout());
+
+        if let Err(err) = result {
+            eprintln!("histrion: {}", err);
+            process::exit(2);
+        }
+
+        return;
+    }
+
+    if outcome.is_ok() {
+        outcome = match options.until {
+            Some(limit) => workspace.simulate_until(limit),
+            None => workspace.simulate(),
+        };
+    }
+
+    // Actors only have names once the sagas have run, so this can't be checked sooner
+    if let Some(name) = &options.rings {
+        if workspace.lookup(name).is_none() {
+            eprintln!("histrion: --rings: no actor named {}\n\n{}", name, USAGE);
+            process::exit(2);
+        }
+    }
+
+    if let Err(err) = write_output(&workspace, &options) {
+        eprintln!("histrion: cannot write output: {}", err);
+        process::exit(2);
+    }
+
+    if workspace.ran_dry() {
+        for listener in workspace.lost_listeners() {
+            eprintln!("histrion: deadlock: {}", deadlock::describe(&workspace, &listener));
+        }
+
+        for watch in workspace.stalled_watches() {
+            eprintln!("histrion: deadlock: {}", deadlock::describe_watch(&workspace, &watch));
+        }
+    }
+
+    if options.check {
+        for problem in workspace.check_continuity() {
+            eprintln!("histrion: continuity: {}", continuity::describe(&workspace, &problem));
+        }
+    }
+
+    if let Err(err) = outcome {
+        eprintln!("histrion: error at {}sec: {}", f64::from(workspace.now()), err);
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item=String>) -> Result<Options, String> {
+    let mut options = Options {
+        format: LogFormat::Text,
+        output: None,
+        timeline: None,
+        causality: None,
+        chronicle: None,
+        trajectories: None,
+        step: None,
+        segments: None,
+        map: None,
+        map_at: None,
+        map_plane: Plane::XY,
+        map_trail: false,
+        rings: None,
+        until: None,
+        seed: 0,
+        runs: None,
+        threads: None,
+        traces_only: false,
+        tag: None,
+        outline: false,
+        on_fault: FaultPolicy::Abort,
+        notify_faults: false,
+        check: false,
+        verbose: false,
+        interactive: false,
+        sagas: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next().ok_or_else(|| format!("{} needs a value", flag))
+        };
+
+        match arg.as_str() {
+            "-f" | "--format" => options.format = value(&arg)?.parse()?,
+            "-o" | "--output" => options.output = Some(value(&arg)?),
+            "-u" | "--until" => {
+                let interval = saga::parse_interval(&value(&arg)?)
+                    .map_err(|err| format!("bad duration: {}", err.message))?;
+                options.until = Some(Instant::default() + interval);
+            },
+            "-s" | "--seed" => {
+                options.seed = value(&arg)?.parse()
+                    .map_err(|err| format!("bad seed: {}", err))?;
+            },
+            "-n" | "--runs" => {
+                options.runs = Some(value(&arg)?.parse()
+                    .map_err(|err| format!("bad run count: {}", err))?);
+            },
+            "--threads" => {
+                options.threads = Some(value(&arg)?.parse()
+                    .map_err(|err| format!("bad thread count: {}", err))?);
+            },
+            "-t" | "--traces" => options.traces_only = true,
+            "--tag" => options.tag = Some(value(&arg)?.trim_start_matches('#').into()),
+            "--outline" => options.outline = true,
+            "--timeline" => options.timeline = Some(value(&arg)?),
+            "--causality" => options.causality = Some(value(&arg)?),
+            "--chronicle" => options.chronicle = Some(value(&arg)?),
+            "--trajectories" => options.trajectories = Some(value(&arg)?),
+            "--step" => {
+                let interval = saga::parse_interval(&value(&arg)?)
+                    .map_err(|err| format!("bad step: {}", err.message))?;
+                options.step = Some(interval);
+            },
+            "--segments" => options.segments = Some(value(&arg)?),
+            "--map" => options.map = Some(value(&arg)?),
+            "--map-at" => {
+                let interval = saga::parse_interval(&value(&arg)?)
+                    .map_err(|err| format!("bad time: {}", err.message))?;
+                options.map_at = Some(Instant::default() + interval);
+            },
+            "--map-plane" => options.map_plane = value(&arg)?.parse()?,
+            "--map-trail" => options.map_trail = true,
+            "--rings" => options.rings = Some(value(&arg)?),
+            "--on-fault" => options.on_fault = value(&arg)?.parse()?,
+            "--notify-faults" => options.notify_faults = true,
+            "-c" | "--check" => options.check = true,
+            "-v" | "--verbose" => options.verbose = true,
+            "-i" | "--interactive" => options.interactive = true,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                process::exit(0);
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            _ => options.sagas.push(arg),
+        }
+    }
+
+    if options.sagas.is_empty() && !options.interactive {
+        return Err("no saga files given".into());
+    }
+
+    if options.runs.is_some() {
+        // An ensemble only writes its summary, so anything else asked for would be lost
+        let ignored = [
+            ("--format", options.format != LogFormat::Text),
+            ("--traces", options.traces_only),
+            ("--tag", options.tag.is_some()),
+            ("--outline", options.outline),
+            ("--timeline", options.timeline.is_some()),
+            ("--causality", options.causality.is_some()),
+            ("--chronicle", options.chronicle.is_some()),
+            ("--trajectories", options.trajectories.is_some()),
+            ("--step", options.step.is_some()),
+            ("--segments", options.segments.is_some()),
+            ("--map", options.map.is_some()),
+            ("--map-at", options.map_at.is_some()),
+            ("--map-plane", options.map_plane != Plane::XY),
+            ("--map-trail", options.map_trail),
+            ("--rings", options.rings.is_some()),
+            ("--on-fault", options.on_fault != FaultPolicy::Abort),
+            ("--notify-faults", options.notify_faults),
+            ("--check", options.check),
+            ("--verbose", options.verbose),
+            ("--interactive", options.interactive),
+        ];
+
+        if let Some((flag, _)) = ignored.iter().find(|(_, given)| *given) {
+            return Err(format!("{} cannot be used with --runs", flag));
+        }
+    }
+
+    Ok(options)
+}
+
+fn write_ensemble(report: &ensemble::EnsembleReport, options: &Options) -> io::Result<()> {
+    const BINS: usize = 10;
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    ensemble::write_summary(report, BINS, &mut out)?;
+    out.flush()
+}
+
+fn write_output(workspace: &Workspace, options: &Options) -> io::Result<()> {
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    if options.traces_only {
+        report::write_traces(workspace, &mut out)?;
+    } else if options.outline {
+        report::write_outline(workspace, &mut out)?;
+    } else if let Some(tag) = &options.tag {
+        report::write_tagged_log(workspace, tag, options.format, &mut out)?;
+    } else {
+        report::write_event_log(workspace, options.format, &mut out)?;
+    }
+
+    out.flush()?;
+
+    if let Some(path) = &options.timeline {
+        let mut out = BufWriter::new(File::create(path)?);
+        timeline::write_svg(workspace, &TimelineStyle::default(), &mut out)?;
+        out.flush()?;
+    }
+
+    if let Some(path) = &options.causality {
+        let mut out = BufWriter::new(File::create(path)?);
+        causality::write_dot(workspace, &mut out)?;
+        out.flush()?;
+    }
+
+    if let Some(path) = &options.chronicle {
+        let mut out = BufWriter::new(File::create(path)?);
+        chronicle::write_markdown(workspace, &mut out)?;
+        out.flush()?;
+    }
+
+    if let Some(path) = &options.trajectories {
+        let format = if path.ends_with(".json") { SampleFormat::Json } else { SampleFormat::Csv };
+
+        let mut sampling = Sampling::across(workspace, 100);
+        if let Some(step) = options.step {
+            sampling.step = step;
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        export::write_samples(workspace, &sampling, format, &mut out)?;
+        out.flush()?;
+    }
+
+    if let Some(path) = &options.segments {
+        let mut out = BufWriter::new(File::create(path)?);
+        export::write_segments(workspace, &mut out)?;
+        out.flush()?;
+    }
+
+    if let Some(path) = &options.map {
+        let mut view = MapView::at(options.map_at.unwrap_or(workspace.now()));
+        view.plane = options.map_plane;
+        view.rings_around = options.rings.as_deref().and_then(|name| workspace.lookup(name));
+
+        if options.map_trail {
+            let mut sampling = Sampling::across(workspace, 100);
+            if let Some(step) = options.step {
+                sampling.step = step;
+            }
+
+            view.span = MapSpan::Trail(sampling);
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        map::write_svg(workspace, &view, &mut out)?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use specs::Entity;
+use vek::{Vec2, Vec3};
+
+use crate::Workspace;
+use crate::export::{self, Sampling};
+use crate::report::{self, xml_escape};
+use crate::time::{Instant, Interval};
+
+/// The plane a map looks down onto, named by the axes it keeps
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Plane {
+    XY,
+    XZ,
+    YZ,
+}
+
+/// What moment, or stretch of time, a map shows
+#[derive(Copy, Clone, Debug)]
+pub enum MapSpan {
+    At(Instant),
+
+    /// Everyone's path across the sampled instants, ending where they were
+    /// at the last one
+    Trail(Sampling),
+}
+
+/// What to draw on a top-down map, and how big, in SVG user units
+#[derive(Copy, Clone, Debug)]
+pub struct MapView {
+    pub plane: Plane,
+    pub span: MapSpan,
+
+    /// Draw light-delay rings centered on this actor
+    pub rings_around: Option<Entity>,
+
+    pub size: f64,
+    pub margin: f64,
+}
+
+struct Mark {
+    actor: Entity,
+    trail: Vec<Vec2<f64>>,
+    alive: bool,
+}
+
+impl Plane {
+    fn project(self, v: Vec3<f64>) -> Vec2<f64> {
+        match self {
+            Plane::XY => Vec2::new(v.x, v.y),
+            Plane::XZ => Vec2::new(v.x, v.z),
+            Plane::YZ => Vec2::new(v.y, v.z),
+        }
+    }
+}
+
+impl FromStr for Plane {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src {
+            "xy" => Ok(Plane::XY),
+            "xz" => Ok(Plane::XZ),
+            "yz" => Ok(Plane::YZ),
+            other => Err(format!("unknown plane: {}", other)),
+        }
+    }
+}
+
+impl MapView {
+    pub fn at(time: Instant) -> Self {
+        MapView {
+            plane: Plane::XY,
+            span: MapSpan::At(time),
+            rings_around: None,
+            size: 800.0,
+            margin: 40.0,
+        }
+    }
+}
+
+/// Draw every actor that has ever moved, as seen from above the chosen plane
+pub fn write_svg(workspace: &Workspace, view: &MapView, out: &mut dyn Write) -> io::Result<()> {
+    let sampling = match view.span {
+        MapSpan::At(time) => Sampling { from: time, until: time, step: Interval::from_f64(0.0) },
+        MapSpan::Trail(sampling) => sampling,
+    };
+
+    let marks = plot(workspace, view.plane, &sampling);
+    let center = view.rings_around.and_then(|actor| {
+        marks.iter().find(|mark| mark.actor == actor)?.trail.last().cloned()
+    });
+
+    let points = marks.iter().flat_map(|mark| mark.trail.iter().cloned());
+    let (low, high) = points.fold(
+        (Vec2::broadcast(f64::INFINITY), Vec2::broadcast(f64::NEG_INFINITY)),
+        |(low, high), point| (Vec2::partial_min(low, point), Vec2::partial_max(high, point)),
+    );
+
+    let (low, high) = if low.x <= high.x { (low, high) } else { (Vec2::zero(), Vec2::zero()) };
+    let extent = (high - low).reduce_partial_max().max(1.0);
+    let middle = (low + high) / 2.0;
+
+    let plot_size = view.size - 2.0 * view.margin;
+    let scale = plot_size / extent;
+    let to_svg = |point: Vec2<f64>| Vec2::new(
+        view.size / 2.0 + (point.x - middle.x) * scale,
+        view.size / 2.0 - (point.y - middle.y) * scale,
+    );
+
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" font-family="sans-serif" font-size="12">"#, view.size)?;
+
+    if let Some(center) = center {
+        let spacing = ring_spacing(extent / 4.0);
+        let origin = to_svg(center);
+
+        for ring in 1 ..= 8 {
+            let radius = spacing * ring as f64;
+            writeln!(out, r#"  <circle class="ring" cx="{:.2}" cy="{:.2}" r="{:.2}" fill="none" stroke="lightsteelblue" stroke-dasharray="4 2"/>"#, origin.x, origin.y, radius * scale)?;
+            writeln!(out, r#"  <text class="ring-label" x="{:.2}" y="{:.2}" fill="lightsteelblue">{}</text>"#, origin.x + radius * scale + 2.0, origin.y, report::duration_text(radius))?;
+        }
+    }
+
+    for mark in marks.iter() {
+        let name = report::actor_text(workspace, mark.actor);
+        let here = to_svg(*mark.trail.last().unwrap());
+        let class = if mark.alive { "actor" } else { "actor dead" };
+        let fill = if mark.alive { "steelblue" } else { "gray" };
+
+        if mark.trail.len() > 1 {
+            let path = mark.trail.iter()
+                .map(|&point| to_svg(point))
+                .map(|point| format!("{:.2},{:.2}", point.x, point.y))
+                .collect::<Vec<_>>().join(" ");
+            writeln!(out, r#"  <polyline class="trail" points="{}" fill="none" stroke="{}"/>"#, path, fill)?;
+        }
+
+        writeln!(out, r#"  <circle class="{}" cx="{:.2}" cy="{:.2}" r="4" fill="{}"><title>{}</title></circle>"#, class, here.x, here.y, fill, xml_escape(&name))?;
+        writeln!(out, r#"  <text class="label" x="{:.2}" y="{:.2}">{}</text>"#, here.x + 6.0, here.y - 6.0, xml_escape(&name))?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+fn plot(workspace: &Workspace, plane: Plane, sampling: &Sampling) -> Vec<Mark> {
+    let mut marks: Vec<Mark> = Vec::new();
+
+    for sample in export::sample(workspace, sampling) {
+        let point = plane.project(sample.position.0);
+
+        match marks.iter_mut().find(|mark| mark.actor == sample.actor) {
+            Some(mark) => {
+                mark.trail.push(point);
+                mark.alive = sample.alive;
+            },
+
+            None => marks.push(Mark {
+                actor: sample.actor,
+                trail: vec![point],
+                alive: sample.alive,
+            }),
+        }
+    }
+
+    marks
+}
+
+/// A round number of light-seconds, 1, 2 or 5 times a power of ten, that is
+/// at least `target`
+fn ring_spacing(target: f64) -> f64 {
+    let magnitude = 10f64.powf(target.log10().floor());
+
+    [1.0, 2.0, 5.0, 10.0].iter()
+        .map(|&factor| factor * magnitude)
+        .find(|&spacing| spacing >= target)
+        .unwrap_or(10.0 * magnitude)
+}
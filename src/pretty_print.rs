@@ -1,8 +1,12 @@
 use std::fmt::{self, Display};
 
-use crate::Position;
+use crate::{Error, Position};
 use crate::action::*;
+use crate::backtrace::Backtrace;
+use crate::history::EventKind;
 use crate::script::Script;
+use crate::time::{Instant, Interval};
+use crate::travel::DriveProfile;
 
 impl Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -21,6 +25,18 @@ impl Display for Action {
                 write!(f, "wait {}sec", f64::from(*interval))
             },
 
+            Action::WaitUntil { at } => {
+                write!(f, "wait until {}sec", f64::from(*at))
+            },
+
+            Action::Every { interval, from, until, .. } => {
+                write!(f, "{} do ...", fmt_every(*interval, *from, *until))
+            },
+
+            Action::WaitNear { subject, distance, target } => {
+                write!(f, "wait until {} within {} of {}", subject, distance, target)
+            },
+
             Action::ListenFor { head, args } => {
                 write!(f, "listen #{}({})", head, args.iter().map(|arg| {
                     format!("{}", arg)
@@ -35,6 +51,10 @@ impl Display for Action {
                 write!(f, "self.accel = {}", Value::from(Position(*value)))
             },
 
+            Action::TravelTo { target, profile } => {
+                write!(f, "travel to {} by {}", target, profile)
+            },
+
             Action::Transmit { head, args } => {
                 write!(f, "transmit #{}({})", head, args.iter().map(|arg| {
                     format!("{}", arg)
@@ -45,6 +65,18 @@ impl Display for Action {
                 write!(f, "die")
             },
 
+            Action::Tag { tag } => {
+                write!(f, "tag #{}", tag)
+            },
+
+            Action::Board { container } => {
+                write!(f, "board {}", container)
+            },
+
+            Action::Disembark => {
+                write!(f, "disembark")
+            },
+
             Action::WriteLocal { name, value } => {
                 write!(f, "{} = {}", name, value)
             },
@@ -61,20 +93,93 @@ impl Display for Action {
 
             Action::Return => {
                 write!(f, "return")
-            }
+            },
+
+            Action::If { condition, skip } => {
+                write!(f, "if {} (else skip {})", condition, skip)
+            },
+
+            Action::Skip { skip } => {
+                write!(f, "skip {}", skip)
+            },
+
+            Action::Annotated { action, annotation } => {
+                write!(f, "{} -- {}", action, annotation)
+            },
 
             //_ => write!(f, "UNIMPLEMENTED"),
         }
     }
 }
 
+impl Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(text) = &self.text {
+            parts.push(fmt_string(text));
+        }
+
+        parts.extend(self.tags.iter().map(|tag| format!("#{}", tag)));
+
+        if let Some(chapter) = &self.chapter {
+            parts.push(format!("chapter {}", fmt_string(chapter)));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Knows { head, args } => {
+                write!(f, "knows #{}({})", head, args.iter().map(|arg| {
+                    format!("{}", arg)
+                }).collect::<Vec<_>>().join(", "))
+            },
+            Condition::Not { condition } => write!(f, "not {}", condition),
+        }
+    }
+}
+
 impl Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expr::Myself => write!(f, "self"),
             Expr::Field { subject, field_name } => write!(f, "{}.{}", subject, field_name),
             Expr::NumConst { value } => write!(f, "{}", value),
-            Expr::Var { name } => write!(f, "{}", name),
+            Expr::Var { name } => write!(f, "{}", fmt_actor_name(name)),
+            Expr::Builtin { func, args } => {
+                write!(f, "{}({})", func.name(), args.iter().map(|arg| {
+                    format!("{}", arg)
+                }).collect::<Vec<_>>().join(", "))
+            },
+            Expr::Random { dist, args } => {
+                write!(f, "{}({})", dist.name(), args.iter().map(|arg| {
+                    format!("{}", arg)
+                }).collect::<Vec<_>>().join(", "))
+            },
+            Expr::Within { center, radius, tag } => {
+                write!(f, "within({}, {}{})", center, radius, fmt_query_tag(tag))
+            },
+            Expr::Nearest { center, count, tag } => {
+                write!(f, "nearest({}, {}{})", center, count, fmt_query_tag(tag))
+            },
+        }
+    }
+}
+
+impl Display for DriveProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DriveProfile::ThrustBrake { accel } => {
+                write!(f, "thrust-brake at {}c/sec", accel)
+            },
+
+            DriveProfile::ThrustCoast { accel, max_speed } => {
+                write!(f, "thrust-coast at {}c/sec up to {}c", accel, max_speed)
+            },
         }
     }
 }
@@ -89,11 +194,138 @@ impl Display for Value {
                     format!("{} = {};", name, value)
                 }).collect::<Vec<String>>().join(" "))
             },
+            Value::List(values) => {
+                write!(f, "[{}]", values.iter().map(|value| {
+                    format!("{}", value)
+                }).collect::<Vec<_>>().join(", "))
+            },
         }
     }
 }
 
-fn fmt_actor_name(name: &str) -> String {
+impl Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}({})", self.head, self.body.iter().map(|value| {
+            format!("{}", value)
+        }).collect::<Vec<_>>().join(", "))
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventKind::Performed { action } => write!(f, "{}", action),
+            EventKind::Traced { expr, value } => write!(f, "trace {} = {}", expr, value),
+            EventKind::Spawned { name, .. } => write!(f, "spawn {}", fmt_actor_name(name)),
+            EventKind::Transmitted { signal } => write!(f, "transmit {}", signal),
+            EventKind::Received { signal, .. } => write!(f, "received {}", signal),
+            EventKind::Drew { dist, args, value } => {
+                write!(f, "draw {}({}) = {}", dist.name(), args.iter().map(|arg| {
+                    format!("{}", arg)
+                }).collect::<Vec<_>>().join(", "), value)
+            },
+            EventKind::Halted => write!(f, "halt"),
+            EventKind::Failed { error } => write!(f, "failed: {}", error.root()),
+            EventKind::Killed { failure } => write!(f, "killed over failure {}", failure),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoSuchGlobal { name } => write!(f, "no such global: {}", name),
+            Error::MissingPosition { name } => write!(f, "{} has no position", name),
+            Error::CouldNotWrite { component } => write!(f, "could not write {} component", component),
+            Error::NoSuchField { name, on_value } => write!(f, "no field {} on {}", name, on_value),
+            Error::NoSuchMethod { name } => write!(f, "no such method: {}", name),
+            Error::ArgListMismatch { name, wanted, got } => {
+                write!(f, "{} takes {} arguments but got {}", name, wanted, got)
+            },
+            Error::NotAnActor { value } => write!(f, "{} is not an actor", value),
+            Error::NoIntercept { target } => write!(f, "cannot intercept {}", target),
+            Error::BadArgument { name, value } => write!(f, "bad argument to {}: {}", name, value),
+            Error::Aboard { name } => write!(f, "{} is aboard another actor and cannot steer", name),
+            Error::CannotBoard { name, container } => {
+                write!(f, "{} cannot board {}, which is aboard it", name, container)
+            },
+            Error::OutOfReach { name, container, distance } => {
+                write!(f, "{} is {}ls from {}, too far to board", name, distance, container)
+            },
+            Error::InFiber { error, backtrace } => write!(f, "{}\n{}", error, backtrace),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let actor = match &self.actor_name {
+            Some(name) => fmt_actor_name(name),
+            None => format!("{:?}", self.actor),
+        };
+
+        write!(f, "  in fiber {} of {} at {}sec", self.fiber, actor, f64::from(self.time))?;
+
+        for frame in self.frames.iter() {
+            let method = match &frame.method {
+                Some(method) => format!("method {}", method),
+                None => "script".to_owned(),
+            };
+
+            write!(f, "\n    {}, action {}", method, frame.pc)?;
+
+            if let Some(action) = &frame.action {
+                write!(f, ": {}", action.unannotated())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_every(interval: Interval, from: Option<Instant>, until: Option<Instant>) -> String {
+    let mut text = format!("every {}sec", f64::from(interval));
+
+    if let Some(from) = from {
+        text.push_str(&format!(" from {}sec", f64::from(from)));
+    }
+
+    if let Some(until) = until {
+        text.push_str(&format!(" until {}sec", f64::from(until)));
+    }
+
+    text
+}
+
+/// Quote a string the way sagas write them
+fn fmt_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            },
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+fn fmt_query_tag(tag: &Option<std::sync::Arc<str>>) -> String {
+    match tag {
+        Some(tag) => format!(", #{}", tag),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn fmt_actor_name(name: &str) -> String {
     if name.contains(' ') {
         format!("[{}]", name)
     } else {
@@ -104,9 +336,7 @@ fn fmt_actor_name(name: &str) -> String {
 impl Script {
     pub fn pretty_print(&self) -> String {
         let mut printer = Printer::default();
-        for action in self.body.iter() {
-            printer.print_action(action);
-        }
+        printer.print_script(&self.body);
         printer.buffer
     }
 }
@@ -125,20 +355,48 @@ impl Printer {
         }
     }
 
-    fn print_action(&mut self, action: &Action) {
+    fn write_line(&mut self, line: &str) {
+        self.write_indent();
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+    }
+
+    fn print_script(&mut self, script: &[Action]) {
+        let mut rest = script;
+        while let Some((action, after)) = rest.split_first() {
+            rest = self.print_action(action, after);
+        }
+    }
+
+    fn print_block(&mut self, header: &str, script: &[Action]) {
+        self.write_line(header);
+        self.indent += 1;
+        self.print_script(script);
+        self.indent -= 1;
+    }
+
+    /// Print one statement, and return the actions that follow it. An `if`
+    /// takes the actions it skips over with it, to rebuild its blocks.
+    fn print_action<'a>(&mut self, action: &Action, rest: &'a [Action]) -> &'a [Action] {
+        let mut rest = rest;
+
+        // Blocks carry their notes on the header line
+        let (action, note) = match action {
+            Action::Annotated { action, annotation } => {
+                (action.as_ref(), format!(" -- {}", annotation))
+            },
+            action => (action, String::new()),
+        };
+
         match action {
             Action::AsActor { name, script } => {
-                self.write_indent();
-                self.buffer.push_str(&format!("as {} do\n", fmt_actor_name(name)));
-                self.indent += 1;
-
-                for action in script.iter() {
-                    self.print_action(action);
-                }
+                self.print_block(&format!("as {} do{}", fmt_actor_name(name), note), script);
+                self.write_line("done");
+            },
 
-                self.indent -= 1;
-                self.write_indent();
-                self.buffer.push_str("done\n");
+            Action::Every { interval, from, until, script } => {
+                self.print_block(&format!("{} do{}", fmt_every(*interval, *from, *until), note), script);
+                self.write_line("done");
             },
 
             Action::DefGlobalMethod { name, body } => {
@@ -146,27 +404,39 @@ impl Printer {
                     .map(|arg| format!("{}", arg))
                     .collect::<Vec<String>>().join(", ");
 
-                self.write_indent();
-                self.buffer.push_str(&format!("def {}({}) do", name, params));
-                self.indent += 1;
+                self.print_block(&format!("def {}({}) do{}", name, params, note), &body.script);
+                self.write_line("done");
+            },
+
+            Action::If { condition, skip } => {
+                let (then, after) = rest.split_at((*skip).min(rest.len()));
+                rest = after;
+
+                let header = format!("if {} do{}", condition, note);
 
-                for action in body.script.iter() {
-                    self.print_action(action);
+                // Only an `else` leaves a skip as the last action of the `then` block
+                match then.split_last() {
+                    Some((Action::Skip { skip }, then)) => {
+                        let (otherwise, after) = rest.split_at((*skip).min(rest.len()));
+                        rest = after;
+
+                        self.print_block(&header, then);
+                        self.print_block("else", otherwise);
+                    },
+
+                    _ => self.print_block(&header, then),
                 }
 
-                self.indent -= 1;
-                self.write_indent();
-                self.buffer.push_str("done\n");
+                self.write_line("done");
             },
 
-            _ => {
-                self.write_indent();
-                self.buffer.push_str(&format!("{}\n", action));
-            },
+            _ => self.write_line(&format!("{}{}", action, note)),
         }
 
         if self.indent == 0 {
             self.buffer.push('\n');
         }
+
+        rest
     }
 }
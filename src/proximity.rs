@@ -0,0 +1,259 @@
+use specs::prelude::*;
+use vek::Vec3;
+
+use crate::{Agenda, Error, Result, Trajectory, Workspace};
+use crate::task::{Fiber, SortToken, Waiting};
+use crate::time::{Instant, Interval};
+
+/// A fiber waiting for two actors to come within `distance` of each other
+pub(crate) struct Watch {
+    actor: Entity,
+    subject: Entity,
+    target: Entity,
+    distance: f64,
+    state: WatchState,
+}
+
+enum WatchState {
+    /// Queued on the actor's agenda for the predicted moment
+    Scheduled(SortToken),
+
+    /// Set aside in the actor's agenda because, on their present courses,
+    /// they never meet
+    Parked(u64),
+}
+
+/// A fiber waiting for two actors to meet, with no meeting in sight
+#[derive(Clone, Debug)]
+pub struct StalledWatch {
+    pub actor: Entity,
+    pub fiber: u64,
+    pub subject: Entity,
+    pub target: Entity,
+    pub distance: f64,
+}
+
+/// The first moment from `from` on when the two trajectories are no more
+/// than `distance` apart, or None if they never will be. Both courses are
+/// piecewise quadratic in time, so within each piece the squared separation
+/// is a quartic, whose first crossing is found directly.
+pub fn first_approach(a: &Trajectory, b: &Trajectory, from: Instant, distance: f64) -> Option<Instant> {
+    let start = f64::from(from);
+
+    let mut breaks = a.phases().into_iter()
+        .chain(b.phases())
+        .map(|(time, _)| time)
+        .filter(|&time| time > start)
+        .collect::<Vec<_>>();
+    breaks.sort_by(f64::total_cmp);
+    breaks.dedup();
+    breaks.push(f64::INFINITY);
+
+    let mut lo = start;
+
+    for hi in breaks {
+        let at = Instant::default() + Interval::from_f64(lo);
+
+        let place = a.sample_at(at).0 - b.sample_at(at).0;
+        let velocity = a.velocity_at(at) - b.velocity_at(at);
+        let accel = accel_at(a, lo) - accel_at(b, lo);
+
+        // |place + velocity τ + accel τ²/2|² - distance²
+        let half = accel * 0.5;
+        let quartic = [
+            place.dot(place) - distance * distance,
+            2.0 * place.dot(velocity),
+            velocity.dot(velocity) + 2.0 * place.dot(half),
+            2.0 * velocity.dot(half),
+            half.dot(half),
+        ];
+
+        if let Some(offset) = first_nonpositive(&quartic, hi - lo) {
+            return Some(at + Interval::from_f64(offset));
+        }
+
+        lo = hi;
+    }
+
+    None
+}
+
+fn accel_at(trajectory: &Trajectory, time: f64) -> Vec3<f64> {
+    trajectory.phases().into_iter()
+        .take_while(|&(since, _)| since <= time)
+        .last()
+        .map_or(Vec3::zero(), |(_, accel)| accel)
+}
+
+/// Smallest τ in [0, limit] where the polynomial is at most zero
+fn first_nonpositive(poly: &[f64], limit: f64) -> Option<f64> {
+    if eval(poly, 0.0) <= 0.0 {
+        return Some(0.0);
+    }
+
+    let limit = limit.min(root_bound(poly));
+    roots(poly, 0.0, limit).into_iter().next()
+}
+
+/// Every root could be no further from zero than this
+fn root_bound(poly: &[f64]) -> f64 {
+    let degree = match poly.iter().rposition(|&c| c != 0.0) {
+        Some(degree) if degree > 0 => degree,
+        _ => return 0.0,
+    };
+
+    let lead = poly[degree].abs();
+    1.0 + poly[.. degree].iter().map(|c| c.abs() / lead).fold(0.0, f64::max)
+}
+
+fn eval(poly: &[f64], x: f64) -> f64 {
+    poly.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+}
+
+/// Roots in [lo, hi], found between the roots of the derivative where the
+/// polynomial can only rise or fall
+fn roots(poly: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    const BISECTIONS: usize = 200;
+
+    let degree = match poly.iter().rposition(|&c| c != 0.0) {
+        Some(degree) if degree > 0 => degree,
+        _ => return Vec::new(),
+    };
+
+    let derivative = (1 ..= degree).map(|i| poly[i] * i as f64).collect::<Vec<_>>();
+
+    let mut stops = vec![lo];
+    stops.extend(roots(&derivative, lo, hi));
+    stops.push(hi);
+
+    let mut found = Vec::new();
+
+    for pair in stops.windows(2) {
+        let (mut a, mut b) = (pair[0], pair[1]);
+        let (fa, fb) = (eval(poly, a), eval(poly, b));
+
+        if fa == 0.0 {
+            found.push(a);
+            continue;
+        }
+
+        if fa * fb > 0.0 {
+            continue;
+        }
+
+        // Keep `b` on the side with the other sign, so the answer errs past the crossing
+        for _ in 0 .. BISECTIONS {
+            let mid = 0.5 * (a + b);
+            if mid <= a || mid >= b {
+                break;
+            }
+
+            if eval(poly, mid) * fa > 0.0 {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+
+        found.push(b);
+    }
+
+    found.dedup();
+    found
+}
+
+impl Workspace {
+    /// Suspend the fiber until `subject` and `target` are within `distance`
+    pub(crate) fn watch(&mut self, fiber: Box<Fiber>, subject: Entity, target: Entity, distance: f64) -> Result<()> {
+        let course = |id| self.world.read_component::<Trajectory>().get(id).cloned().unwrap_or_default();
+        let eta = first_approach(&course(subject), &course(target), self.now, distance);
+
+        let actor = fiber.me;
+        let state = match eta {
+            Some(eta) => WatchState::Scheduled(self.schedule(fiber, eta)?),
+
+            None => {
+                let guid = self.make_guid();
+                self.world.write_component::<Agenda>()
+                    .get_mut(actor)
+                    .ok_or(Error::CouldNotWrite { component: "Agenda" })?
+                    .park(Waiting { guid, fiber });
+
+                WatchState::Parked(guid)
+            },
+        };
+
+        self.watches.push(Watch { actor, subject, target, distance, state });
+        Ok(())
+    }
+
+    /// Work out again when the watches on `id` come due, now that its course
+    /// has changed. Watches whose fiber has since run, or been cancelled, are
+    /// dropped along the way.
+    pub(crate) fn rewatch(&mut self, id: Entity) -> Result<()> {
+        for watch in std::mem::take(&mut self.watches) {
+            let involved = watch.subject == id || watch.target == id;
+
+            let fiber = {
+                let mut agendas = self.world.write_component::<Agenda>();
+                let agenda = match agendas.get_mut(watch.actor) {
+                    Some(agenda) => agenda,
+                    None => continue,
+                };
+
+                let held = match watch.state {
+                    WatchState::Scheduled(token) => agenda.next.as_ref().map(|task| task.token) == Some(token),
+                    WatchState::Parked(guid) => agenda.watching.as_ref().map(|waiting| waiting.guid) == Some(guid),
+                };
+
+                if !held {
+                    continue;
+                }
+
+                if !involved {
+                    drop(agendas);
+                    self.watches.push(watch);
+                    continue;
+                }
+
+                match watch.state {
+                    WatchState::Scheduled(_) => agenda.next.take().unwrap().fiber,
+                    WatchState::Parked(_) => agenda.watching.take().unwrap().fiber,
+                }
+            };
+
+            self.watch(fiber, watch.subject, watch.target, watch.distance)?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop every watch the actor's fibers are waiting on
+    pub(crate) fn forget_watches(&mut self, actor: Entity) {
+        self.watches.retain(|watch| watch.actor != actor);
+    }
+
+    /// Every fiber set aside waiting for actors who, on their present
+    /// courses, never meet
+    pub fn stalled_watches(&self) -> Vec<StalledWatch> {
+        let agendas = self.world.read_component::<Agenda>();
+
+        self.watches.iter().filter_map(|watch| {
+            let guid = match watch.state {
+                WatchState::Parked(guid) => guid,
+                WatchState::Scheduled(_) => return None,
+            };
+
+            let waiting = agendas.get(watch.actor)?.watching.as_ref()
+                .filter(|waiting| waiting.guid == guid)?;
+
+            Some(StalledWatch {
+                actor: watch.actor,
+                fiber: waiting.fiber.id,
+                subject: watch.subject,
+                target: watch.target,
+                distance: watch.distance,
+            })
+        }).collect()
+    }
+}
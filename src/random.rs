@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use ordered_float::NotNan;
+
+use crate::{Error, Result, Workspace};
+use crate::action::{Expr, Value};
+use crate::history::EventKind;
+use crate::task::Fiber;
+
+/// Random draws that scripts can make from any expression
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Distribution {
+    /// Uniform between two numbers
+    Uniform,
+
+    /// Normal, given the mean and standard deviation
+    Normal,
+
+    /// One of the arguments, each equally likely
+    Choice,
+}
+
+/// xoshiro256**, seeded through splitmix64. Written out here rather than
+/// pulled from a crate so a seed gives the same story on every version.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: [u64; 4],
+}
+
+impl Distribution {
+    pub const ALL: &'static [Distribution] = &[
+        Distribution::Uniform,
+        Distribution::Normal,
+        Distribution::Choice,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Uniform => "uniform",
+            Distribution::Normal => "normal",
+            Distribution::Choice => "choice",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Distribution::ALL.iter().cloned().find(|dist| dist.name() == name)
+    }
+}
+
+impl Rng {
+    pub(crate) fn seeded(seed: u64) -> Self {
+        let mut x = seed;
+        let mut splitmix = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+
+        Rng {
+            state: [splitmix(), splitmix(), splitmix(), splitmix()],
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Workspace {
+    /// A fresh workspace whose random draws all follow from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut workspace = Workspace::new();
+        workspace.seed = seed;
+        workspace.rng = Rng::seeded(seed);
+        workspace
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Evaluate on behalf of the host rather than the story, as the debugger
+    /// and `evaluate` do. Random draws come from a copy of the generator and
+    /// are not logged, so looking at a run doesn't change how it goes on.
+    pub(crate) fn eval_aside(&mut self, fiber: &Fiber, expr: &Expr) -> Result<Value> {
+        let rng = self.rng.clone();
+        let aside = std::mem::replace(&mut self.aside, true);
+
+        let value = self.eval_expr(fiber, expr);
+
+        self.aside = aside;
+        self.rng = rng;
+        value
+    }
+
+    /// Make a random draw, and log it in the history unless evaluating aside
+    pub(crate) fn draw(&mut self, fiber: &Fiber, dist: Distribution, args: Vec<Value>) -> Result<Value> {
+        let wanted = match dist {
+            Distribution::Uniform | Distribution::Normal => 2,
+            Distribution::Choice => args.len().max(1),
+        };
+
+        if args.len() != wanted {
+            return Err(Error::ArgListMismatch {
+                name: dist.name().into(),
+                wanted,
+                got: args.len(),
+            });
+        }
+
+        let number = |value: &Value| match value {
+            &Value::Num(n) if n.is_finite() => Ok(n.into_inner()),
+            other => Err(Error::BadArgument {
+                name: dist.name().into(),
+                value: other.clone(),
+            }),
+        };
+
+        let value = match dist {
+            Distribution::Uniform => {
+                let (low, high) = (number(&args[0])?, number(&args[1])?);
+                let value = low + (high - low) * self.rng.next_f64();
+
+                // Finite bounds can still be far enough apart to overflow
+                Value::Num(NotNan::new(value).map_err(|_err| Error::BadArgument {
+                    name: dist.name().into(),
+                    value: Value::List(args.clone().into()),
+                })?)
+            },
+
+            Distribution::Normal => {
+                let (mean, deviation) = (number(&args[0])?, number(&args[1])?);
+
+                if deviation < 0.0 {
+                    return Err(Error::BadArgument {
+                        name: dist.name().into(),
+                        value: args[1].clone(),
+                    });
+                }
+
+                // Box-Muller, keeping the first draw away from zero for the log
+                let u = 1.0 - self.rng.next_f64();
+                let v = self.rng.next_f64();
+                let z = (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos();
+
+                Value::Num(NotNan::new(mean + deviation * z).map_err(|_err| Error::BadArgument {
+                    name: dist.name().into(),
+                    value: Value::List(args.clone().into()),
+                })?)
+            },
+
+            Distribution::Choice => {
+                let index = (self.rng.next_f64() * args.len() as f64) as usize;
+                args[index.min(args.len() - 1)].clone()
+            },
+        };
+
+        if !self.aside {
+            let args: Arc<[Value]> = args.into();
+            self.record(fiber, EventKind::Drew { dist, args, value: value.clone() });
+        }
+
+        Ok(value)
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{Liveness, Workspace};
+use crate::action::Value;
+use crate::import::Loader;
+use crate::report;
+use crate::saga;
+use crate::time::Instant;
+
+const HELP: &str = "\
+Enter saga statements to perform them as Everything, or one of:
+    :step               run the next queued task
+    :run                run until the simulation halts
+    :run-until <time>   run until the given time, e.g. 2y
+    :time               show the current time
+    :actors             list actors with their positions
+    :globals            list global names and session locals
+    :agenda             list what each actor is waiting for
+    :help               show this message
+    :quit               leave the REPL";
+
+/// An interactive session around a live workspace
+pub struct Repl {
+    workspace: Workspace,
+    locals: HashMap<Arc<str>, Value>,
+    pending: String,
+    seen: usize,
+
+    /// Remembers what has been imported, so each file is only loaded once
+    loader: Loader,
+}
+
+/// What the REPL has to say after a line of input
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reply {
+    Output(String),
+    NeedMore,
+    Quit,
+}
+
+impl Repl {
+    pub fn new(workspace: Workspace) -> Self {
+        Repl::with_loader(workspace, Loader::new())
+    }
+
+    /// Start a session that shares imports with the given loader, so files
+    /// it already loaded are not performed twice
+    pub fn with_loader(mut workspace: Workspace, loader: Loader) -> Self {
+        workspace.set_echo(false);
+        let seen = workspace.history().len();
+
+        Repl {
+            workspace,
+            locals: HashMap::new(),
+            pending: String::new(),
+            seen,
+            loader,
+        }
+    }
+
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        if self.pending.is_empty() { "> " } else { ".. " }
+    }
+
+    pub fn handle_line(&mut self, line: &str) -> Reply {
+        if self.pending.is_empty() && line.trim_start().starts_with(':') {
+            return self.command(line.trim());
+        }
+
+        self.pending.push_str(line);
+        self.pending.push('\n');
+
+        let script = match saga::parse(&self.pending) {
+            Ok(script) => script,
+            Err(err) if err.incomplete => return Reply::NeedMore,
+            Err(err) => {
+                self.pending.clear();
+                return Reply::Output(format!("parse error: {}", err.message));
+            },
+        };
+
+        self.pending.clear();
+
+        // Imports typed at the prompt are found relative to where we are
+        let script = match self.loader.resolve(script, Path::new(".")) {
+            Ok(script) => script,
+            Err(err) => return Reply::Output(format!("import error: {}", err)),
+        };
+
+        let outcome = self.workspace.perform_with(script.into_inner(), &mut self.locals);
+        self.report(outcome)
+    }
+
+    fn command(&mut self, line: &str) -> Reply {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let arg = words.collect::<Vec<_>>().join(" ");
+
+        match command {
+            ":quit" | ":q" => Reply::Quit,
+
+            ":help" => Reply::Output(HELP.into()),
+
+            ":time" => Reply::Output(format!("{}sec", f64::from(self.workspace.now()))),
+
+            ":step" => {
+                if self.workspace.has_halted() {
+                    return Reply::Output("the simulation has halted".into());
+                }
+
+                let outcome = self.workspace.update();
+                self.report(outcome)
+            },
+
+            ":run" => {
+                let outcome = self.workspace.simulate();
+                self.report(outcome)
+            },
+
+            ":run-until" => {
+                let limit = match saga::parse_interval(&arg) {
+                    Ok(interval) => Instant::default() + interval,
+                    Err(err) => return Reply::Output(format!("bad time: {}", err.message)),
+                };
+
+                let outcome = self.workspace.simulate_until(limit);
+                self.report(outcome)
+            },
+
+            ":actors" => Reply::Output(self.list_actors()),
+
+            ":globals" => Reply::Output(self.list_globals()),
+
+            ":agenda" => Reply::Output(self.list_agenda()),
+
+            other => Reply::Output(format!("unknown command {} (try :help)", other)),
+        }
+    }
+
+    /// Show every event since the last report, plus any error
+    fn report(&mut self, outcome: crate::Result<()>) -> Reply {
+        let events = &self.workspace.history().events()[self.seen ..];
+
+        let mut lines = events.iter().map(|event| {
+            format!(
+                "{:<8.0}: {}: {}",
+                f64::from(event.time),
+                report::actor_text(&self.workspace, event.actor),
+                report::event_text(&self.workspace, &event.kind),
+            )
+        }).collect::<Vec<_>>();
+
+        self.seen = self.workspace.history().len();
+
+        if let Err(err) = outcome {
+            lines.push(format!("error: {}", err));
+        }
+
+        Reply::Output(lines.join("\n"))
+    }
+
+    fn list_actors(&self) -> String {
+        self.workspace.globals().into_iter().map(|(name, id)| {
+            let position = match self.workspace.position_of(id) {
+                Ok(position) => report::value_text(&self.workspace, &position.into()),
+                Err(err) => format!("({})", err),
+            };
+
+            let liveness = match self.workspace.liveness_of(id) {
+                Liveness::Alive => "",
+                Liveness::Dead => " (dead)",
+            };
+
+            format!("{} at {}{}", name, position, liveness)
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    fn list_globals(&self) -> String {
+        let mut locals = self.locals.iter().collect::<Vec<_>>();
+        locals.sort_by_key(|&(name, _)| name);
+
+        let locals = locals.into_iter().map(|(name, value)| {
+            format!("{} = {}", name, report::value_text(&self.workspace, value))
+        });
+
+        let globals = self.workspace.globals().into_iter().map(|(name, id)| {
+            format!("{} = {}", name, report::actor_text(&self.workspace, id))
+        });
+
+        locals.chain(globals).collect::<Vec<_>>().join("\n")
+    }
+
+    fn list_agenda(&self) -> String {
+        let stalled = self.workspace.stalled_watches();
+
+        self.workspace.globals().into_iter().filter_map(|(name, id)| {
+            let mut plans = Vec::new();
+
+            if let Some(eta) = self.workspace.next_task_of(id) {
+                plans.push(format!("next task at {}sec", f64::from(eta)));
+            }
+
+            for signal in self.workspace.listening_for(id) {
+                plans.push(format!("listening for {}", report::signal_text(&self.workspace, &signal)));
+            }
+
+            for watch in stalled.iter().filter(|watch| watch.actor == id) {
+                plans.push(format!(
+                    "waiting for {} to come within {} of {}",
+                    report::actor_text(&self.workspace, watch.subject),
+                    watch.distance,
+                    report::actor_text(&self.workspace, watch.target),
+                ));
+            }
+
+            if plans.is_empty() {
+                None
+            } else {
+                Some(format!("{}: {}", name, plans.join("; ")))
+            }
+        }).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Read lines from `input` until it runs dry or the user quits
+pub fn run(repl: &mut Repl, input: &mut dyn BufRead, output: &mut dyn Write) -> io::Result<()> {
+    let mut line = String::new();
+
+    loop {
+        write!(output, "{}", repl.prompt())?;
+        output.flush()?;
+
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        match repl.handle_line(line.trim_end_matches(['\n', '\r'])) {
+            Reply::Output(text) if text.is_empty() => (),
+            Reply::Output(text) => writeln!(output, "{}", text)?,
+            Reply::NeedMore => (),
+            Reply::Quit => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::Workspace;
+use crate::action::{Annotation, Signal, Value};
+use crate::history::{Event, EventId, EventKind};
+use crate::pretty_print::fmt_actor_name;
+use crate::script::TimeUnit;
+
+/// How to write out the event log
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("unknown log format: {}", other)),
+        }
+    }
+}
+
+/// Write every recorded event
+pub fn write_event_log(workspace: &Workspace, format: LogFormat, out: &mut dyn Write) -> io::Result<()> {
+    let events = workspace.history().events().iter().enumerate().collect::<Vec<_>>();
+    write_events(workspace, &events, format, out)
+}
+
+/// Write only the events caused by actions carrying the given tag
+pub fn write_tagged_log(workspace: &Workspace, tag: &str, format: LogFormat, out: &mut dyn Write) -> io::Result<()> {
+    let events = workspace.history().tagged(tag).collect::<Vec<_>>();
+    write_events(workspace, &events, format, out)
+}
+
+fn write_events(workspace: &Workspace, events: &[(EventId, &Event)], format: LogFormat, out: &mut dyn Write) -> io::Result<()> {
+    match format {
+        LogFormat::Text => {
+            for &(_, event) in events {
+                writeln!(out, "{}", event_line(workspace, event))?;
+            }
+        },
+
+        LogFormat::Json => {
+            writeln!(out, "[")?;
+
+            for (index, &(id, event)) in events.iter().enumerate() {
+                let separator = if index + 1 < events.len() { "," } else { "" };
+                writeln!(out, "  {}{}", event_json(workspace, id, event), separator)?;
+            }
+
+            writeln!(out, "]")?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Write only the values of `trace` actions
+pub fn write_traces(workspace: &Workspace, out: &mut dyn Write) -> io::Result<()> {
+    for event in workspace.history().events() {
+        if let EventKind::Traced { .. } = event.kind {
+            writeln!(out, "{}", event_line(workspace, event))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the story as told by its annotations: the prose of each annotated
+/// action, under the chapter it belongs to
+pub fn write_outline(workspace: &Workspace, out: &mut dyn Write) -> io::Result<()> {
+    let mut chapter = None;
+    let mut last: Option<(&Event, &Arc<Annotation>)> = None;
+
+    for event in workspace.history().events() {
+        let annotation = match &event.annotation {
+            Some(annotation) => annotation,
+            None => continue,
+        };
+
+        // One action can record several events; tell it once
+        if let Some((previous, note)) = last {
+            if Arc::ptr_eq(note, annotation) && previous.fiber == event.fiber && previous.time == event.time {
+                continue;
+            }
+        }
+
+        last = Some((event, annotation));
+
+        let text = match &annotation.text {
+            Some(text) => text,
+            None => continue,
+        };
+
+        if annotation.chapter.is_some() && annotation.chapter != chapter {
+            chapter = annotation.chapter.clone();
+            writeln!(out, "\n## Chapter {}\n", chapter.as_deref().unwrap_or_default())?;
+        }
+
+        writeln!(
+            out,
+            "- {}, {}: {}",
+            duration_text(f64::from(event.time)),
+            actor_text(workspace, event.actor),
+            text,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn event_line(workspace: &Workspace, event: &Event) -> String {
+    let line = format!(
+        "{:<8.0}: {}: {}",
+        f64::from(event.time),
+        actor_text(workspace, event.actor),
+        event_text(workspace, &event.kind),
+    );
+
+    match &event.annotation {
+        Some(annotation) => format!("{} -- {}", line, annotation),
+        None => line,
+    }
+}
+
+fn event_json(workspace: &Workspace, id: usize, event: &Event) -> String {
+    let kind = match event.kind {
+        EventKind::Performed { .. } => "performed",
+        EventKind::Traced { .. } => "traced",
+        EventKind::Spawned { .. } => "spawned",
+        EventKind::Transmitted { .. } => "transmitted",
+        EventKind::Received { .. } => "received",
+        EventKind::Drew { .. } => "drew",
+        EventKind::Halted => "halted",
+        EventKind::Failed { .. } => "failed",
+        EventKind::Killed { .. } => "killed",
+    };
+
+    let mut json = format!(
+        "{{\"id\": {}, \"time\": {}, \"actor\": {}, \"fiber\": {}, \"kind\": \"{}\", \"text\": {}",
+        id,
+        f64::from(event.time),
+        json_string(&actor_text(workspace, event.actor)),
+        event.fiber,
+        kind,
+        json_string(&event_text(workspace, &event.kind)),
+    );
+
+    if let EventKind::Received { transmission, .. } = event.kind {
+        json.push_str(&format!(", \"transmission\": {}", transmission));
+    }
+
+    if let EventKind::Killed { failure } = event.kind {
+        json.push_str(&format!(", \"failure\": {}", failure));
+    }
+
+    if let Some(annotation) = &event.annotation {
+        if let Some(text) = &annotation.text {
+            json.push_str(&format!(", \"note\": {}", json_string(text)));
+        }
+
+        if !annotation.tags.is_empty() {
+            json.push_str(&format!(", \"tags\": [{}]", annotation.tags.iter().map(|tag| {
+                json_string(tag)
+            }).collect::<Vec<_>>().join(", ")));
+        }
+
+        if let Some(chapter) = &annotation.chapter {
+            json.push_str(&format!(", \"chapter\": {}", json_string(chapter)));
+        }
+    }
+
+    json.push('}');
+    json
+}
+
+/// Describe an event, naming actors instead of showing their ids
+pub(crate) fn event_text(workspace: &Workspace, kind: &EventKind) -> String {
+    match kind {
+        EventKind::Traced { expr, value } => {
+            format!("trace {} = {}", expr, value_text(workspace, value))
+        },
+
+        EventKind::Transmitted { signal } => {
+            format!("transmit {}", signal_text(workspace, signal))
+        },
+
+        EventKind::Received { signal, .. } => {
+            format!("received {}", signal_text(workspace, signal))
+        },
+
+        EventKind::Drew { dist, args, value } => {
+            format!("draw {}({}) = {}", dist.name(), args.iter().map(|arg| {
+                value_text(workspace, arg)
+            }).collect::<Vec<_>>().join(", "), value_text(workspace, value))
+        },
+
+        other => format!("{}", other),
+    }
+}
+
+pub(crate) fn actor_text(workspace: &Workspace, id: specs::Entity) -> String {
+    match workspace.name_of(id) {
+        Some(name) => fmt_actor_name(&name),
+        None => format!("{:?}", id),
+    }
+}
+
+pub(crate) fn value_text(workspace: &Workspace, value: &Value) -> String {
+    match value {
+        &Value::ActorId(id) => actor_text(workspace, id),
+
+        Value::Struct(fields) => {
+            format!("{{ {} }}", fields.iter().map(|(name, value)| {
+                format!("{} = {};", name, value_text(workspace, value))
+            }).collect::<Vec<String>>().join(" "))
+        },
+
+        Value::List(values) => {
+            format!("[{}]", values.iter().map(|value| {
+                value_text(workspace, value)
+            }).collect::<Vec<_>>().join(", "))
+        },
+
+        other => format!("{}", other),
+    }
+}
+
+pub(crate) fn signal_text(workspace: &Workspace, signal: &Signal) -> String {
+    format!("#{}({})", signal.head, signal.body.iter().map(|value| {
+        value_text(workspace, value)
+    }).collect::<Vec<_>>().join(", "))
+}
+
+pub(crate) fn json_string(src: &str) -> String {
+    let mut json = String::with_capacity(src.len() + 2);
+    json.push('"');
+
+    for c in src.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+pub(crate) fn xml_escape(src: &str) -> String {
+    src.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A number of seconds in whichever unit reads most naturally
+pub(crate) fn duration_text(secs: f64) -> String {
+    let units = [
+        (TimeUnit::Year, "yr"),
+        (TimeUnit::Day, "day"),
+        (TimeUnit::Hour, "hr"),
+        (TimeUnit::Min, "min"),
+    ];
+
+    let (scale, unit) = units.iter()
+        .map(|&(unit, name)| (f64::from(unit), name))
+        .find(|&(scale, _)| secs.abs() >= 2.0 * scale)
+        .unwrap_or((1.0, "sec"));
+
+    format!("{}{}", (secs / scale * 10.0).round() / 10.0, unit)
+}
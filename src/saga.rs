@@ -0,0 +1,913 @@
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use vek::Vec3;
+
+use crate::action::*;
+use crate::builtins::Builtin;
+use crate::random::Distribution;
+use crate::script::{AccelUnit, Import, LenUnit, Script, TimeExpr, TimeUnit};
+use crate::time::{Instant, Interval};
+use crate::travel::DriveProfile;
+
+/// Something wrong with the text of a saga
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+
+    /// Whether the input ended too soon, so more text might fix it
+    pub incomplete: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(Arc<str>),
+    Name(Arc<str>),
+    Number(f64),
+    Str(Arc<str>),
+    Punct(char),
+}
+
+struct Lexeme {
+    token: Token,
+    line: usize,
+}
+
+struct Parser {
+    lexemes: Vec<Lexeme>,
+    cursor: usize,
+    imports: Vec<Import>,
+
+    /// Set by a `chapter` statement, for everything after it in the same block
+    chapter: Option<Arc<str>>,
+}
+
+/// Attach the notes from a block's `do` line, if it had any
+fn noted(action: Action, note: Option<Annotation>) -> Action {
+    match note {
+        Some(annotation) if !annotation.is_empty() => Action::Annotated {
+            action: action.into(),
+            annotation: annotation.into(),
+        },
+        _ => action,
+    }
+}
+
+fn time_unit(name: &str) -> Option<TimeUnit> {
+    Some(match name {
+        "s" | "sec" | "secs" | "second" | "seconds" => TimeUnit::Sec,
+        "min" | "mins" | "minute" | "minutes" => TimeUnit::Min,
+        "h" | "hr" | "hrs" | "hour" | "hours" => TimeUnit::Hour,
+        "d" | "day" | "days" => TimeUnit::Day,
+        "w" | "wk" | "week" | "weeks" => TimeUnit::Week,
+        "y" | "yr" | "yrs" | "year" | "years" => TimeUnit::Year,
+        _ => return None,
+    })
+}
+
+fn len_unit(name: &str) -> Option<LenUnit> {
+    Some(match name {
+        "km" => LenUnit::Km,
+        "ls" | "lsec" | "lsecs" => LenUnit::LightSec,
+        "lmin" | "lmins" => LenUnit::LightMin,
+        "au" | "AU" => LenUnit::AstronomicalUnit,
+        _ => return None,
+    })
+}
+
+/// Parse the text of a saga into a script
+pub fn parse(src: &str) -> Result<Script, ParseError> {
+    let mut parser = Parser {
+        lexemes: tokenize(src)?,
+        cursor: 0,
+        imports: Vec::new(),
+        chapter: None,
+    };
+
+    let body = parser.parse_block(None)?;
+
+    Ok(Script {
+        body: body.into(),
+        imports: parser.imports.into(),
+    })
+}
+
+/// Parse a bare duration like `1hr` or `2.5 years`
+pub fn parse_interval(src: &str) -> Result<Interval, ParseError> {
+    let mut parser = Parser {
+        lexemes: tokenize(src)?,
+        cursor: 0,
+        imports: Vec::new(),
+        chapter: None,
+    };
+
+    let interval = parser.parse_interval()?;
+    parser.expect_end()?;
+    Ok(interval)
+}
+
+fn tokenize(src: &str) -> Result<Vec<Lexeme>, ParseError> {
+    let mut lexemes = Vec::new();
+
+    for (index, text) in src.lines().enumerate() {
+        let line = index + 1;
+        let mut chars = text.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c == '/' && chars.clone().nth(1) == Some('/') {
+                // A comment runs to the end of the line, unless it's inside a string
+                break;
+            } else if c.is_ascii_digit() {
+                let mut number = String::new();
+
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign = (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+
+                    if c.is_ascii_digit() || c == '.' || exponent_sign {
+                        number.push(c);
+                        chars.next();
+                    } else if c == 'e' || c == 'E' {
+                        let mut ahead = chars.clone();
+                        ahead.next();
+                        match ahead.next() {
+                            Some(d) if d.is_ascii_digit() || d == '-' || d == '+' => {
+                                number.push(c);
+                                chars.next();
+                            },
+                            _ => break,
+                        }
+                    } else {
+                        break;
+                    }
+                }
+
+                let value = number.parse::<f64>().map_err(|_| ParseError {
+                    line,
+                    message: format!("malformed number {:?}", number),
+                    incomplete: false,
+                })?;
+
+                lexemes.push(Lexeme { token: Token::Number(value), line });
+            } else if c.is_alphabetic() || c == '_' {
+                let mut ident = String::new();
+
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                lexemes.push(Lexeme { token: Token::Ident(ident.into()), line });
+            } else if c == '[' {
+                chars.next();
+                let mut name = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(ParseError {
+                            line,
+                            message: "unterminated actor name".into(),
+                            incomplete: false,
+                        }),
+                    }
+                }
+
+                lexemes.push(Lexeme { token: Token::Name(name.into()), line });
+            } else if c == '"' {
+                chars.next();
+                let mut text = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some(c) => text.push(c),
+                            None => continue,
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err(ParseError {
+                            line,
+                            message: "unterminated string".into(),
+                            incomplete: false,
+                        }),
+                    }
+                }
+
+                lexemes.push(Lexeme { token: Token::Str(text.into()), line });
+            } else if "#(),=.{};:/-+".contains(c) {
+                chars.next();
+                lexemes.push(Lexeme { token: Token::Punct(c), line });
+            } else {
+                return Err(ParseError {
+                    line,
+                    message: format!("unexpected character {:?}", c),
+                    incomplete: false,
+                });
+            }
+        }
+    }
+
+    Ok(lexemes)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.lexemes.get(self.cursor).map(|lexeme| &lexeme.token)
+    }
+
+    fn peek_ahead(&self, offset: usize) -> Option<&Token> {
+        self.lexemes.get(self.cursor + offset).map(|lexeme| &lexeme.token)
+    }
+
+    fn line(&self) -> usize {
+        self.lexemes.get(self.cursor)
+            .or_else(|| self.lexemes.last())
+            .map_or(1, |lexeme| lexeme.line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line(),
+            message: message.into(),
+            incomplete: self.peek().is_none(),
+        })
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident.as_ref() == keyword)
+    }
+
+    fn at_punct(&self, punct: char) -> bool {
+        self.peek() == Some(&Token::Punct(punct))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.cursor += 1;
+        }
+        found
+    }
+
+    fn eat_punct(&mut self, punct: char) -> bool {
+        let found = self.at_punct(punct);
+        if found {
+            self.cursor += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", keyword))
+        }
+    }
+
+    fn expect_punct(&mut self, punct: char) -> Result<(), ParseError> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", punct))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => self.error("unexpected trailing input"),
+        }
+    }
+
+    fn ident(&mut self) -> Result<Arc<str>, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Ident(ident)) => {
+                self.cursor += 1;
+                Ok(ident)
+            },
+
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    fn actor_name(&mut self) -> Result<Arc<str>, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Ident(name)) | Some(Token::Name(name)) => {
+                self.cursor += 1;
+                self.qualified(name)
+            },
+
+            _ => self.error("expected an actor name"),
+        }
+    }
+
+    /// The rest of a name like `stars::Sol`, given its first part
+    fn qualified(&mut self, first: Arc<str>) -> Result<Arc<str>, ParseError> {
+        let mut name = first.to_string();
+
+        while self.at_punct(':') && self.peek_ahead(1) == Some(&Token::Punct(':')) {
+            self.cursor += 2;
+
+            match self.peek().cloned() {
+                Some(Token::Ident(part)) | Some(Token::Name(part)) => {
+                    self.cursor += 1;
+                    name.push_str("::");
+                    name.push_str(&part);
+                },
+
+                _ => return self.error("expected a name after `::`"),
+            }
+        }
+
+        Ok(name.into())
+    }
+
+    fn string(&mut self) -> Result<Arc<str>, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Str(text)) => {
+                self.cursor += 1;
+                Ok(text)
+            },
+
+            _ => self.error("expected a quoted string"),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let sign = if self.eat_punct('-') { -1.0 } else { 1.0 };
+
+        match self.peek().cloned() {
+            Some(Token::Number(value)) => {
+                self.cursor += 1;
+                Ok(sign * value)
+            },
+
+            _ => self.error("expected a number"),
+        }
+    }
+
+    /// Statements up to `terminator`, or to the end of input if there is none
+    fn parse_block(&mut self, terminator: Option<&str>) -> Result<Vec<Action>, ParseError> {
+        let mut body = Vec::new();
+        let chapter = self.chapter.clone();
+
+        loop {
+            match terminator {
+                Some(keyword) if self.eat_keyword(keyword) => break,
+                None if self.peek().is_none() => break,
+                None if self.at_keyword("import") => self.parse_import()?,
+                Some(keyword) if self.peek().is_none() => {
+                    return self.error(format!("expected `{}` before end of input", keyword));
+                },
+                _ => self.parse_statement(&mut body)?,
+            }
+        }
+
+        self.chapter = chapter;
+        Ok(body)
+    }
+
+    /// One statement, which may become several actions, and its annotation
+    fn parse_statement(&mut self, body: &mut Vec<Action>) -> Result<(), ParseError> {
+        if self.at_keyword("chapter") && self.peek_ahead(1) != Some(&Token::Punct('=')) {
+            self.cursor += 1;
+            self.chapter = Some(self.chapter_name()?);
+            return Ok(());
+        }
+
+        let start = body.len();
+        let chapter = self.chapter.clone();
+
+        if self.eat_keyword("if") {
+            self.parse_if(body)?;
+        } else {
+            body.push(self.parse_action()?);
+        }
+
+        // A block may have notes on its `do` line as well as after `done`
+        let (action, mut annotation) = match body[start].clone() {
+            Action::Annotated { action, annotation } => (action.as_ref().clone(), annotation.as_ref().clone()),
+            action => (action, Annotation::default()),
+        };
+
+        if let Some(trailing) = self.parse_annotation()? {
+            if annotation.text.is_some() && trailing.text.is_some() {
+                return self.error("a statement can only have one piece of prose");
+            }
+
+            annotation.text = annotation.text.or(trailing.text);
+            annotation.tags.extend(trailing.tags);
+            annotation.chapter = trailing.chapter.or(annotation.chapter);
+        }
+
+        if annotation.chapter.is_none() {
+            annotation.chapter = chapter;
+        }
+
+        if !annotation.is_empty() {
+            body[start] = Action::Annotated {
+                action: action.into(),
+                annotation: annotation.into(),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// `-- "prose" #tag chapter 2`, up to the end of the line
+    fn parse_annotation(&mut self) -> Result<Option<Annotation>, ParseError> {
+        if !self.at_punct('-') || self.peek_ahead(1) != Some(&Token::Punct('-')) {
+            return Ok(None);
+        }
+
+        let line = self.line();
+        self.cursor += 2;
+
+        let mut annotation = Annotation::default();
+
+        while self.peek().is_some() && self.line() == line {
+            if self.eat_punct('#') {
+                annotation.tags.push(self.ident()?);
+            } else if self.eat_keyword("chapter") {
+                annotation.chapter = Some(self.chapter_name()?);
+            } else if let (Some(Token::Str(text)), None) = (self.peek().cloned(), &annotation.text) {
+                self.cursor += 1;
+                annotation.text = Some(text);
+            } else {
+                return self.error("expected prose, a #tag or `chapter` in annotation");
+            }
+        }
+
+        Ok(Some(annotation))
+    }
+
+    /// The `do` opening a block, and any notes on the rest of its line
+    fn expect_do(&mut self) -> Result<Option<Annotation>, ParseError> {
+        self.expect_keyword("do")?;
+        self.parse_annotation()
+    }
+
+    fn chapter_name(&mut self) -> Result<Arc<str>, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Ident(name)) | Some(Token::Str(name)) => {
+                self.cursor += 1;
+                Ok(name)
+            },
+
+            Some(Token::Number(number)) => {
+                self.cursor += 1;
+                Ok(number.to_string().into())
+            },
+
+            _ => self.error("expected a chapter name or number"),
+        }
+    }
+
+    fn parse_import(&mut self) -> Result<(), ParseError> {
+        let line = self.line();
+        self.expect_keyword("import")?;
+        let path = self.string()?;
+
+        let namespace = if self.eat_keyword("as") {
+            Some(self.ident()?)
+        } else {
+            None
+        };
+
+        self.imports.push(Import { path, namespace, line });
+        Ok(())
+    }
+
+    fn parse_action(&mut self) -> Result<Action, ParseError> {
+        let keyword = match self.peek().cloned() {
+            Some(Token::Ident(keyword)) => keyword,
+            _ => return self.error("expected a statement"),
+        };
+
+        if self.peek_ahead(1) == Some(&Token::Punct('=')) {
+            self.cursor += 2;
+            let value = self.parse_expr()?.into();
+            return Ok(Action::WriteLocal { name: keyword, value });
+        }
+
+        self.cursor += 1;
+
+        Ok(match keyword.as_ref() {
+            "halt" => Action::Halt,
+            "die" => Action::Die,
+            "disembark" => Action::Disembark,
+            "return" => Action::Return,
+
+            "trace" => Action::Trace {
+                expr: self.parse_expr()?.into(),
+            },
+
+            "tag" => {
+                self.expect_punct('#')?;
+                Action::Tag { tag: self.ident()? }
+            },
+
+            "board" => Action::Board {
+                container: self.parse_expr()?.into(),
+            },
+
+            "spawn" => Action::Spawn {
+                name: self.actor_name()?,
+            },
+
+            "wait" if self.eat_keyword("until") => {
+                if self.at_instant() {
+                    return Ok(Action::WaitUntil { at: self.parse_instant()? });
+                }
+
+                let subject = self.parse_expr()?.into();
+                self.expect_keyword("within")?;
+                let distance = self.parse_distance()?.into();
+                self.expect_keyword("of")?;
+                let target = self.parse_expr()?.into();
+                Action::WaitNear { subject, distance, target }
+            },
+
+            "every" => {
+                let interval = self.parse_interval()?;
+                let from = if self.eat_keyword("from") { Some(self.parse_instant()?) } else { None };
+                let until = if self.eat_keyword("until") { Some(self.parse_instant()?) } else { None };
+                let note = self.expect_do()?;
+                let script = self.parse_block(Some("done"))?.into();
+                noted(Action::Every { interval, from, until, script }, note)
+            },
+
+            "wait" => Action::Wait {
+                interval: self.parse_interval()?,
+            },
+
+            "listen" => {
+                let (head, args) = self.parse_signal()?;
+                Action::ListenFor { head, args }
+            },
+
+            "transmit" => {
+                let (head, args) = self.parse_signal()?;
+                Action::Transmit { head, args }
+            },
+
+            "as" => {
+                let name = self.actor_name()?;
+                let note = self.expect_do()?;
+                let script = self.parse_block(Some("done"))?.into();
+                noted(Action::AsActor { name, script }, note)
+            },
+
+            "def" => {
+                let name = self.ident()?;
+                self.expect_punct('(')?;
+
+                let mut params = Vec::new();
+                while !self.eat_punct(')') {
+                    if !params.is_empty() {
+                        self.expect_punct(',')?;
+                    }
+                    params.push(self.ident()?);
+                }
+
+                let note = self.expect_do()?;
+                let mut script = self.parse_block(Some("done"))?;
+
+                // Methods only hand control back to the caller via `return`
+                if !matches!(script.last(), Some(Action::Return)) {
+                    script.push(Action::Return);
+                }
+
+                noted(Action::DefGlobalMethod {
+                    name,
+                    body: Method {
+                        params: params.into(),
+                        script: script.into(),
+                    }.into(),
+                }, note)
+            },
+
+            "call" => {
+                let name = self.ident()?;
+                let name = self.qualified(name)?;
+                let args = self.parse_args()?;
+                Action::Call { name, args }
+            },
+
+            "self" => {
+                self.expect_punct('.')?;
+                self.expect_keyword("accel")?;
+                self.expect_punct('=')?;
+                Action::SetAccel {
+                    value: self.parse_accel_vector()?,
+                }
+            },
+
+            "import" => {
+                self.cursor -= 1;
+                return self.error("`import` is only allowed at the top level");
+            },
+
+            "travel" => {
+                self.expect_keyword("to")?;
+                let target = self.parse_expr()?.into();
+                self.expect_keyword("by")?;
+                let profile = self.parse_drive_profile()?;
+                Action::TravelTo { target, profile }
+            },
+
+            other => {
+                self.cursor -= 1;
+                return self.error(format!("unknown statement `{}`", other));
+            },
+        })
+    }
+
+    /// `if [not] knows #signal do ... [else ...] done`, flattened into
+    /// actions that skip over whichever block doesn't apply
+    fn parse_if(&mut self, body: &mut Vec<Action>) -> Result<(), ParseError> {
+        let condition = self.parse_condition()?.into();
+        let note = self.expect_do()?;
+        let chapter = self.chapter.clone();
+
+        let mut then = Vec::new();
+        while !self.at_keyword("else") && !self.at_keyword("done") {
+            if self.peek().is_none() {
+                return self.error("expected `done` before end of input");
+            }
+            self.parse_statement(&mut then)?;
+        }
+
+        self.chapter = chapter;
+
+        let otherwise = if self.eat_keyword("else") {
+            self.parse_block(Some("done"))?
+        } else {
+            self.expect_keyword("done")?;
+            Vec::new()
+        };
+
+        if otherwise.is_empty() {
+            body.push(noted(Action::If { condition, skip: then.len() }, note));
+            body.extend(then);
+        } else {
+            body.push(noted(Action::If { condition, skip: then.len() + 1 }, note));
+            body.extend(then);
+            body.push(Action::Skip { skip: otherwise.len() });
+            body.extend(otherwise);
+        }
+
+        Ok(())
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, ParseError> {
+        if self.eat_keyword("not") {
+            return Ok(Condition::Not {
+                condition: self.parse_condition()?.into(),
+            });
+        }
+
+        self.expect_keyword("knows")?;
+        let (head, args) = self.parse_signal()?;
+        Ok(Condition::Knows { head, args })
+    }
+
+    fn parse_signal(&mut self) -> Result<(Arc<str>, Arc<[Expr]>), ParseError> {
+        self.expect_punct('#')?;
+        let head = self.ident()?;
+
+        let args = if self.at_punct('(') {
+            self.parse_args()?
+        } else {
+            Vec::new().into()
+        };
+
+        Ok((head, args))
+    }
+
+    fn parse_args(&mut self) -> Result<Arc<[Expr]>, ParseError> {
+        self.expect_punct('(')?;
+
+        let mut args = Vec::new();
+        while !self.eat_punct(')') {
+            if !args.is_empty() {
+                self.expect_punct(',')?;
+            }
+            args.push(self.parse_expr()?);
+        }
+
+        Ok(args.into())
+    }
+
+    fn parse_interval(&mut self) -> Result<Interval, ParseError> {
+        let number = self.number()?;
+        let unit = self.ident()?;
+
+        let unit = match time_unit(&unit) {
+            Some(unit) => unit,
+            None => {
+                self.cursor -= 1;
+                return self.error(format!("unknown time unit `{}`", unit));
+            },
+        };
+
+        Ok(TimeExpr::Constant { number, unit }.into())
+    }
+
+    /// A number with a length unit like `1 AU`, or any expression in
+    /// light-seconds
+    fn parse_distance(&mut self) -> Result<Expr, ParseError> {
+        let offset = if self.peek() == Some(&Token::Punct('-')) { 1 } else { 0 };
+        let unit_follows = matches!(self.peek_ahead(offset), Some(Token::Number(_)))
+            && matches!(self.peek_ahead(offset + 1), Some(Token::Ident(unit)) if len_unit(unit).is_some());
+
+        if !unit_follows {
+            return self.parse_expr();
+        }
+
+        let number = self.number()?;
+        let unit = len_unit(&self.ident()?).unwrap();
+        Ok(Expr::NumConst { value: number * f64::from(unit) })
+    }
+
+    fn at_instant(&self) -> bool {
+        self.at_keyword("year") || matches!(self.peek(), Some(Token::Number(_)) | Some(Token::Punct('-')))
+    }
+
+    /// `year 3 day 12`, a bare year like `2300`, or a duration since the
+    /// start like `10 days`. Years and days count from 1, as in chronicles.
+    fn parse_instant(&mut self) -> Result<Instant, ParseError> {
+        let unit_follows = matches!(self.peek_ahead(1), Some(Token::Ident(unit)) if time_unit(unit).is_some());
+
+        if !self.at_keyword("year") && unit_follows {
+            return Ok(Instant::default() + self.parse_interval()?);
+        }
+
+        self.eat_keyword("year");
+        let year = self.number()?;
+        let day = if self.eat_keyword("day") { self.number()? } else { 1.0 };
+
+        let since_start = (year - 1.0) * f64::from(TimeUnit::Year) + (day - 1.0) * f64::from(TimeUnit::Day);
+        Ok(Instant::default() + Interval::from_f64(since_start))
+    }
+
+    /// An acceleration in `g` or `c/sec`, defaulting to the latter
+    fn parse_accel_unit(&mut self) -> Result<f64, ParseError> {
+        if self.eat_keyword("g") {
+            Ok(f64::from(AccelUnit::Gee))
+        } else if self.at_keyword("c") && self.peek_ahead(1) == Some(&Token::Punct('/')) {
+            self.cursor += 2;
+            self.expect_keyword("sec")?;
+            Ok(f64::from(AccelUnit::CeePerSec))
+        } else {
+            Ok(f64::from(AccelUnit::CeePerSec))
+        }
+    }
+
+    fn parse_accel_vector(&mut self) -> Result<Vec3<f64>, ParseError> {
+        let mut value = Vec3::zero();
+
+        if self.eat_punct('{') {
+            while !self.eat_punct('}') {
+                let axis = self.ident()?;
+                self.expect_punct('=')?;
+                let number = self.number()?;
+                self.expect_punct(';')?;
+
+                match axis.as_ref() {
+                    "x" => value.x = number,
+                    "y" => value.y = number,
+                    "z" => value.z = number,
+                    _ => return self.error(format!("no such axis `{}`", axis)),
+                }
+            }
+        } else {
+            self.expect_punct('(')?;
+            value.x = self.number()?;
+            self.expect_punct(',')?;
+            value.y = self.number()?;
+            self.expect_punct(',')?;
+            value.z = self.number()?;
+            self.expect_punct(')')?;
+        }
+
+        Ok(value * self.parse_accel_unit()?)
+    }
+
+    fn parse_drive_profile(&mut self) -> Result<DriveProfile, ParseError> {
+        let kind = self.ident()?;
+        self.expect_punct('-')?;
+        let mode = self.ident()?;
+        self.expect_keyword("at")?;
+        let accel = self.number()? * self.parse_accel_unit()?;
+
+        match (kind.as_ref(), mode.as_ref()) {
+            ("thrust", "brake") => Ok(DriveProfile::ThrustBrake { accel }),
+
+            ("thrust", "coast") => {
+                self.expect_keyword("up")?;
+                self.expect_keyword("to")?;
+                let max_speed = self.number()?;
+                self.expect_keyword("c")?;
+                Ok(DriveProfile::ThrustCoast { accel, max_speed })
+            },
+
+            _ => self.error(format!("unknown drive profile `{}-{}`", kind, mode)),
+        }
+    }
+
+    /// `within(center, radius [, #tag])` or `nearest(center, count [, #tag])`
+    fn parse_query(&mut self, kind: &str) -> Result<Expr, ParseError> {
+        self.expect_punct('(')?;
+        let center = self.parse_expr()?.into();
+        self.expect_punct(',')?;
+        let amount = self.parse_expr()?.into();
+
+        let tag = if self.eat_punct(',') {
+            self.expect_punct('#')?;
+            Some(self.ident()?)
+        } else {
+            None
+        };
+
+        self.expect_punct(')')?;
+
+        Ok(match kind {
+            "within" => Expr::Within { center, radius: amount, tag },
+            _ => Expr::Nearest { center, count: amount, tag },
+        })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = match self.peek().cloned() {
+            Some(Token::Number(_)) | Some(Token::Punct('-')) => Expr::NumConst {
+                value: self.number()?,
+            },
+
+            Some(Token::Name(name)) => {
+                self.cursor += 1;
+                Expr::Var { name: self.qualified(name)? }
+            },
+
+            Some(Token::Ident(ident)) => {
+                self.cursor += 1;
+
+                if ident.as_ref() == "self" {
+                    Expr::Myself
+                } else if matches!(ident.as_ref(), "within" | "nearest") && self.at_punct('(') {
+                    self.parse_query(&ident)?
+                } else if let (Some(dist), true) = (Distribution::from_name(&ident), self.at_punct('(')) {
+                    Expr::Random { dist, args: self.parse_args()? }
+                } else if self.at_punct('(') {
+                    let func = match Builtin::from_name(&ident) {
+                        Some(func) => func,
+                        None => {
+                            self.cursor -= 1;
+                            return self.error(format!("no such function `{}`", ident));
+                        },
+                    };
+
+                    Expr::Builtin { func, args: self.parse_args()? }
+                } else {
+                    Expr::Var { name: self.qualified(ident)? }
+                }
+            },
+
+            Some(Token::Punct('(')) => {
+                self.cursor += 1;
+                let expr = self.parse_expr()?;
+                self.expect_punct(')')?;
+                expr
+            },
+
+            _ => return self.error("expected an expression"),
+        };
+
+        while self.eat_punct('.') {
+            expr = Expr::Field {
+                subject: expr.into(),
+                field_name: self.ident()?,
+            };
+        }
+
+        Ok(expr)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use specs::prelude::*;
+
+use crate::{Agenda, Error, Result, Workspace};
+use crate::action::{Action, Value};
+use crate::history::EventId;
+use crate::task::{Fiber, Recurrence, SortToken};
+use crate::time::{Instant, Interval};
+
+impl Workspace {
+    /// Queue `script` to be performed by the fiber's actor, with the fiber's
+    /// locals, every `interval`. The first time is `from`, or the first step
+    /// after it if that has passed, or now if there is no `from`.
+    pub(crate) fn start_recurrence(
+        &mut self,
+        fiber: &Fiber,
+        interval: Interval,
+        from: Option<Instant>,
+        until: Option<Instant>,
+        script: Arc<[Action]>,
+        origin: EventId,
+    ) -> Result<()> {
+        let step = f64::from(interval);
+        if step <= 0.0 {
+            return Err(Error::BadArgument {
+                name: "every".into(),
+                value: Value::Num(step.into()),
+            });
+        }
+
+        let mut first = from.unwrap_or(self.now);
+        if first < self.now {
+            let missed = (f64::from(first.delta(self.now)) / step).ceil();
+            first = first + Interval::from_f64(missed * step);
+        }
+
+        let recurrence = Recurrence {
+            token: SortToken { eta: first, guid: 0 },
+            first,
+            count: 0,
+            every: interval,
+            until,
+            script,
+            locals: fiber.frame().unwrap().locals.clone(),
+            origin,
+        };
+
+        self.queue_recurrence(fiber.me, recurrence, first)
+    }
+
+    /// Start this occurrence's fiber, and queue the next occurrence if there
+    /// is one
+    pub(crate) fn recur(&mut self, me: Entity, recurrence: Recurrence) -> Result<Box<Fiber>> {
+        let mut fiber = self.new_fiber(me, recurrence.script.clone());
+        fiber.frame_mut().unwrap().locals = recurrence.locals.clone();
+        self.history.record_fiber_origin(fiber.id, recurrence.origin);
+
+        let mut recurrence = recurrence;
+        recurrence.count += 1;
+
+        let step = f64::from(recurrence.every);
+        let next = recurrence.first + Interval::from_f64(step * recurrence.count as f64);
+
+        // The new fiber has yet to do anything, so it has no frames to show
+        self.queue_recurrence(me, recurrence, next)
+            .map_err(|err| self.fail_in(me, fiber.id, Vec::new(), err))?;
+
+        Ok(fiber)
+    }
+
+    fn queue_recurrence(&mut self, me: Entity, mut recurrence: Recurrence, eta: Instant) -> Result<()> {
+        // A hair of slack, so `until` still counts when it's a whole number of steps away
+        let slack = f64::from(recurrence.every) * 1e-9;
+        if recurrence.until.is_some_and(|until| f64::from(eta) - f64::from(until) > slack) {
+            return Ok(());
+        }
+
+        let token = SortToken { eta, guid: self.make_guid() };
+        recurrence.token = token;
+
+        self.world.write_component::<Agenda>()
+            .get_mut(me)
+            .ok_or(Error::CouldNotWrite { component: "Agenda" })?
+            .recurring.push(recurrence);
+
+        self.enqueue(token, me);
+        Ok(())
+    }
+}
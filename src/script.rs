@@ -6,6 +6,15 @@ use crate::time::Interval;
 #[derive(Clone, Debug)]
 pub struct Script {
     pub(crate) body: Arc<[Action]>,
+    pub(crate) imports: Arc<[Import]>,
+}
+
+/// An `import "file" [as name]` line, not yet resolved
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub path: Arc<str>,
+    pub namespace: Option<Arc<str>>,
+    pub line: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -28,7 +37,10 @@ pub enum TimeUnit {
 
 #[derive(Copy, Clone, Debug)]
 pub enum LenUnit {
+    Km,
     LightSec,
+    LightMin,
+    AstronomicalUnit,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -44,12 +56,20 @@ pub enum AccelUnit {
 
 impl Script {
     pub fn new(body: Arc<[Action]>) -> Self {
-        Script { body }
+        Script {
+            body,
+            imports: Vec::new().into(),
+        }
     }
 
     pub fn into_inner(&self) -> Arc<[Action]> {
         self.body.clone()
     }
+
+    /// Files this script imports. Use `import::Loader` to pull them in.
+    pub fn imports(&self) -> &[Import] {
+        &self.imports
+    }
 }
 
 impl From<TimeExpr> for Interval {
@@ -77,6 +97,19 @@ impl From<TimeUnit> for f64 {
     }
 }
 
+impl From<LenUnit> for f64 {
+    fn from(unit: LenUnit) -> Self {
+        use LenUnit::*;
+
+        match unit {
+            Km => 1.0 / 299_792.458,
+            LightSec => 1.0,
+            LightMin => 60.0,
+            AstronomicalUnit => 149_597_870.7 / 299_792.458,
+        }
+    }
+}
+
 impl From<AccelUnit> for f64 {
     fn from(unit: AccelUnit) -> Self {
         use AccelUnit::*;
@@ -178,6 +211,6 @@ impl Default for Script {
             Action::Halt,
         ].into();
 
-        Script { body }
+        Script::new(body)
     }
 }
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use specs::prelude::*;
+use specs::{Component, VecStorage};
+use vek::Vec3;
+
+use crate::{Error, Liveness, Position, Result, Trajectory, Workspace};
+use crate::action::Value;
+
+/// Labels an actor gives itself with `tag #star`, for queries to pick out
+#[derive(Clone, Debug, Default, Component)]
+#[storage(VecStorage)]
+pub struct Tags(pub Vec<Arc<str>>);
+
+/// Where every living actor is at one instant, arranged as a k-d tree so
+/// neighborhood queries don't have to look at everyone
+#[derive(Clone, Debug, Default)]
+pub struct SpatialIndex {
+    /// Each slice's middle element splits the rest along axis `depth % 3`
+    points: Vec<(Vec3<f64>, Entity)>,
+}
+
+impl SpatialIndex {
+    pub fn new(mut points: Vec<(Vec3<f64>, Entity)>) -> Self {
+        arrange(&mut points, 0);
+        SpatialIndex { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Everyone within `radius` of `center`, nearest first
+    pub fn within(&self, center: Vec3<f64>, radius: f64) -> Vec<(f64, Entity)> {
+        let mut found = Vec::new();
+        within(&self.points, 0, center, radius, &mut found);
+        found.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        found
+    }
+
+    /// The `count` nearest to `center` that pass `test`, nearest first
+    pub fn nearest(&self, center: Vec3<f64>, count: usize, test: impl Fn(Entity) -> bool) -> Vec<(f64, Entity)> {
+        let mut best = Vec::with_capacity(count + 1);
+        if count > 0 {
+            nearest(&self.points, 0, center, count, &test, &mut best);
+        }
+        best
+    }
+}
+
+fn arrange(points: &mut [(Vec3<f64>, Entity)], depth: usize) {
+    if points.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % 3;
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+    let (below, above) = points.split_at_mut(mid);
+    arrange(below, depth + 1);
+    arrange(&mut above[1 ..], depth + 1);
+}
+
+fn within(points: &[(Vec3<f64>, Entity)], depth: usize, center: Vec3<f64>, radius: f64, found: &mut Vec<(f64, Entity)>) {
+    if points.is_empty() {
+        return;
+    }
+
+    let mid = points.len() / 2;
+    let (place, id) = points[mid];
+
+    let distance = place.distance(center);
+    if distance <= radius {
+        found.push((distance, id));
+    }
+
+    let offset = center[depth % 3] - place[depth % 3];
+
+    if offset - radius <= 0.0 {
+        within(&points[.. mid], depth + 1, center, radius, found);
+    }
+
+    if offset + radius >= 0.0 {
+        within(&points[mid + 1 ..], depth + 1, center, radius, found);
+    }
+}
+
+fn nearest(
+    points: &[(Vec3<f64>, Entity)],
+    depth: usize,
+    center: Vec3<f64>,
+    count: usize,
+    test: &impl Fn(Entity) -> bool,
+    best: &mut Vec<(f64, Entity)>,
+) {
+    if points.is_empty() {
+        return;
+    }
+
+    let mid = points.len() / 2;
+    let (place, id) = points[mid];
+
+    if test(id) {
+        let candidate = (place.distance(center), id);
+        let at = best.partition_point(|&(distance, other)| (distance, other) < candidate);
+        best.insert(at, candidate);
+        best.truncate(count);
+    }
+
+    let offset = center[depth % 3] - place[depth % 3];
+    let (near, far) = if offset <= 0.0 {
+        (&points[.. mid], &points[mid + 1 ..])
+    } else {
+        (&points[mid + 1 ..], &points[.. mid])
+    };
+
+    nearest(near, depth + 1, center, count, test, best);
+
+    // The far side can only help if the splitting plane is closer than the worst kept
+    if best.len() < count || offset.abs() <= best[best.len() - 1].0 {
+        nearest(far, depth + 1, center, count, test, best);
+    }
+}
+
+impl Workspace {
+    /// Tags the actor has given itself
+    pub fn tags_of(&self, id: Entity) -> Vec<Arc<str>> {
+        self.world.read_component::<Tags>().get(id)
+            .map(|tags| tags.0.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn add_tag(&mut self, id: Entity, tag: Arc<str>) -> Result<()> {
+        let mut tags = self.world.write_component::<Tags>();
+        let entry = tags.entry(id).map_err(|_err| Error::CouldNotWrite { component: "Tags" })?;
+        let tags = entry.or_insert_with(Tags::default);
+
+        if !tags.0.contains(&tag) {
+            tags.0.push(tag);
+        }
+
+        Ok(())
+    }
+
+    /// Where every living actor is now, built on first use after the clock
+    /// moves or anyone's course changes
+    pub fn spatial_index(&mut self) -> Result<&SpatialIndex> {
+        if self.spatial.is_none() {
+            let actors = {
+                let entities = self.world.entities();
+                let trajectories = self.world.read_component::<Trajectory>();
+                let liveness = self.world.read_component::<Liveness>();
+
+                (&entities, &trajectories).join()
+                    .filter(|&(id, _)| !matches!(liveness.get(id), Some(Liveness::Dead)))
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>()
+            };
+
+            let mut points = Vec::with_capacity(actors.len());
+            for id in actors {
+                let Position(place) = self.get_position(id)?;
+                points.push((place, id));
+            }
+
+            self.spatial = Some(SpatialIndex::new(points));
+        }
+
+        Ok(self.spatial.as_ref().unwrap())
+    }
+
+    /// Actors within `radius` light-seconds of `center`, nearest first,
+    /// leaving out `center` itself if it is an actor
+    pub(crate) fn eval_within(&mut self, center: Value, radius: Value, tag: Option<&str>) -> Result<Value> {
+        let radius = match radius {
+            Value::Num(radius) if radius.into_inner() >= 0.0 => radius.into_inner(),
+            other => return Err(Error::BadArgument { name: "within".into(), value: other }),
+        };
+
+        let (place, myself) = self.query_center("within", center)?;
+
+        let found = self.spatial_index()?.within(place, radius);
+        let tags = self.world.read_component::<Tags>();
+
+        Ok(Value::List(found.into_iter()
+            .filter(|&(_, id)| Some(id) != myself && has_tag(&tags, id, tag))
+            .map(|(_, id)| Value::ActorId(id))
+            .collect()))
+    }
+
+    /// The `count` actors nearest to `center`, nearest first, leaving out
+    /// `center` itself if it is an actor
+    pub(crate) fn eval_nearest(&mut self, center: Value, count: Value, tag: Option<&str>) -> Result<Value> {
+        let count = match count {
+            Value::Num(count) if count.into_inner() >= 0.0 && count.into_inner().fract() == 0.0 => count.into_inner() as usize,
+            other => return Err(Error::BadArgument { name: "nearest".into(), value: other }),
+        };
+
+        let (place, myself) = self.query_center("nearest", center)?;
+
+        self.spatial_index()?;
+        let index = self.spatial.as_ref().unwrap();
+        let tags = self.world.read_component::<Tags>();
+
+        let found = index.nearest(place, count, |id| Some(id) != myself && has_tag(&tags, id, tag));
+        Ok(Value::List(found.into_iter().map(|(_, id)| Value::ActorId(id)).collect()))
+    }
+
+    /// Where a query is centered, and the actor there if it was given one
+    fn query_center(&mut self, name: &str, center: Value) -> Result<(Vec3<f64>, Option<Entity>)> {
+        match center {
+            Value::ActorId(id) => Ok((self.get_position(id)?.0, Some(id))),
+
+            Value::Struct(ref fields) => {
+                let coord = |axis: &str| match fields.get(axis) {
+                    Some(&Value::Num(n)) => Ok(n.into_inner()),
+                    _ => Err(Error::BadArgument { name: name.into(), value: center.clone() }),
+                };
+
+                Ok((Vec3::new(coord("x")?, coord("y")?, coord("z")?), None))
+            },
+
+            other => Err(Error::BadArgument { name: name.into(), value: other }),
+        }
+    }
+}
+
+fn has_tag(tags: &ReadStorage<Tags>, id: Entity, tag: Option<&str>) -> bool {
+    match tag {
+        Some(tag) => tags.get(id).is_some_and(|tags| tags.0.iter().any(|other| other.as_ref() == tag)),
+        None => true,
+    }
+}
@@ -3,13 +3,16 @@ use std::sync::Arc;
 
 use specs::Entity;
 
-use crate::action::{Action, Value};
-use crate::time::Instant;
+use crate::action::{Action, Signal, Value};
+use crate::history::EventId;
+use crate::time::{Instant, Interval};
 
 #[derive(Clone)]
 pub struct Fiber {
+    pub(crate) id: u64,
     pub(crate) me: Entity,
     pub(crate) stack: Vec<StackFrame>,
+    pub(crate) woken_by: Option<(Signal, EventId)>,
 }
 
 #[derive(Clone)]
@@ -17,6 +20,7 @@ pub struct StackFrame {
     pub(crate) pc: usize,
     pub(crate) script: Arc<[Action]>,
     pub(crate) locals: HashMap<Arc<str>, Value>,
+    pub(crate) method: Option<Arc<str>>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -31,6 +35,42 @@ pub struct QueuedTask {
     pub(crate) fiber: Box<Fiber>,
 }
 
+/// What became of a fiber after performing one action
+pub(crate) enum Step {
+    Continue(Box<Fiber>),
+    Enter { parent: Box<Fiber>, child: Box<Fiber> },
+    Suspended,
+    Finished(Box<Fiber>),
+}
+
+/// What a fiber is to do once its action is performed
+pub(crate) enum Outcome {
+    Continue,
+    Enter(Box<Fiber>),
+    Resume { at: Instant },
+    Watch { subject: Entity, target: Entity, distance: f64 },
+    Listen(Signal),
+}
+
+/// A script an actor performs over and over, each time in a fresh fiber
+#[derive(Clone)]
+pub struct Recurrence {
+    pub(crate) token: SortToken,
+
+    /// When it first came due, and how many times it has since. Each time
+    /// is counted out from the first so that rounding doesn't build up.
+    pub(crate) first: Instant,
+    pub(crate) count: u32,
+
+    pub(crate) every: Interval,
+    pub(crate) until: Option<Instant>,
+    pub(crate) script: Arc<[Action]>,
+    pub(crate) locals: HashMap<Arc<str>, Value>,
+
+    /// The `every` event that set it going
+    pub(crate) origin: EventId,
+}
+
 #[derive(Clone)]
 pub struct Waiting {
     pub(crate) guid: u64,
@@ -53,16 +93,19 @@ impl From<QueuedTask> for (Instant, Box<Fiber>) {
 }
 
 impl Fiber {
-    pub(crate) fn new(me: Entity, script: Arc<[Action]>) -> Self {
+    pub(crate) fn new(id: u64, me: Entity, script: Arc<[Action]>) -> Self {
         Fiber {
+            id,
             me,
             stack: vec![
                 StackFrame {
                     pc: 0,
                     script,
                     locals: HashMap::new(),
+                    method: None,
                 },
             ],
+            woken_by: None,
         }
     }
 
@@ -74,6 +117,11 @@ impl Fiber {
         self.stack.last_mut()
     }
 
+    pub(crate) fn peek(&self) -> Option<&Action> {
+        let frame = self.stack.last()?;
+        frame.script.get(frame.pc)
+    }
+
     pub(crate) fn fetch(&mut self) -> Option<Action> {
         let frame = self.stack.last_mut()?;
         let action = frame.script.get(frame.pc)?.clone();
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use specs::Entity;
+
+use crate::Workspace;
+use crate::action::Action;
+use crate::history::EventKind;
+use crate::report::{self, xml_escape};
+use crate::time::{Instant, Interval};
+
+/// Layout of a Gantt-style timeline, in SVG user units
+#[derive(Copy, Clone, Debug)]
+pub struct TimelineStyle {
+    pub width: f64,
+    pub lane_height: f64,
+    pub label_width: f64,
+    pub margin: f64,
+}
+
+struct Lane {
+    actor: Entity,
+    start: Instant,
+    end: Instant,
+}
+
+impl Default for TimelineStyle {
+    fn default() -> Self {
+        TimelineStyle {
+            width: 1200.0,
+            lane_height: 40.0,
+            label_width: 140.0,
+            margin: 20.0,
+        }
+    }
+}
+
+/// Draw one lane per actor, from its creation to its death or the end of the
+/// recorded history, with the signals between them drawn as arrows
+pub fn write_svg(workspace: &Workspace, style: &TimelineStyle, out: &mut dyn Write) -> io::Result<()> {
+    let events = workspace.history().events();
+    let end = workspace.now();
+
+    let mut lanes: Vec<Lane> = Vec::new();
+    let mut lane_of: HashMap<Entity, usize> = HashMap::new();
+
+    for event in events {
+        let actors = match event.kind {
+            EventKind::Spawned { child, .. } => vec![event.actor, child],
+            _ => vec![event.actor],
+        };
+
+        for actor in actors {
+            lane_of.entry(actor).or_insert_with(|| {
+                let start = workspace.creation_date_of(actor).unwrap_or_default();
+                lanes.push(Lane { actor, start, end });
+                lanes.len() - 1
+            });
+        }
+
+        if event.kind.is_death() {
+            let lane = &mut lanes[lane_of[&event.actor]];
+            lane.end = lane.end.min(event.time);
+        }
+    }
+
+    let start = lanes.iter().map(|lane| lane.start).min().unwrap_or_default();
+    let span = f64::from(start.delta(end)).max(1.0);
+
+    let plot_left = style.margin + style.label_width;
+    let plot_width = style.width - plot_left - style.margin;
+    let axis_y = style.margin + lanes.len() as f64 * style.lane_height;
+    let height = axis_y + style.margin * 2.0;
+
+    let x = |time: Instant| plot_left + f64::from(start.delta(time)) / span * plot_width;
+    let y = |lane: usize| style.margin + (lane as f64 + 0.5) * style.lane_height;
+
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#, style.width, height)?;
+    writeln!(out, r#"  <defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#)?;
+
+    for (index, lane) in lanes.iter().enumerate() {
+        let name = report::actor_text(workspace, lane.actor);
+        writeln!(out, r#"  <text class="label" x="{}" y="{}" dominant-baseline="middle">{}</text>"#, style.margin, y(index), xml_escape(&name))?;
+        writeln!(out, r#"  <line class="lane" x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="lightgray" stroke-width="8"/>"#, x(lane.start), y(index), x(lane.end), y(index))?;
+    }
+
+    for (id, event) in events.iter().enumerate() {
+        let lane = lane_of[&event.actor];
+        let (cx, cy) = (x(event.time), y(lane));
+        let title = format!("{}sec: {}", f64::from(event.time), report::event_text(workspace, &event.kind));
+
+        match &event.kind {
+            EventKind::Performed { action: Action::Wait { interval } } => {
+                let until = x((event.time + *interval).min(lanes[lane].end));
+                writeln!(out, r#"  <rect class="wait" x="{:.2}" y="{:.2}" width="{:.2}" height="6" fill="steelblue"><title>{}</title></rect>"#, cx, cy - 3.0, (until - cx).max(1.0), xml_escape(&title))?;
+            },
+
+            EventKind::Transmitted { .. } => {
+                writeln!(out, r#"  <circle class="transmit" cx="{:.2}" cy="{:.2}" r="5" fill="darkorange"><title>{}</title></circle>"#, cx, cy, xml_escape(&title))?;
+            },
+
+            EventKind::Received { transmission, .. } => {
+                writeln!(out, r#"  <circle class="receive" cx="{:.2}" cy="{:.2}" r="5" fill="white" stroke="darkorange" stroke-width="2"><title>{}</title></circle>"#, cx, cy, xml_escape(&title))?;
+
+                if let Some(sent) = workspace.history().get(*transmission) {
+                    draw_arrow(out, (x(sent.time), y(lane_of[&sent.actor])), (cx, cy), id)?;
+                }
+            },
+
+            EventKind::Traced { .. } => {
+                writeln!(out, r#"  <rect class="trace" x="{:.2}" y="{:.2}" width="8" height="8" fill="seagreen" transform="rotate(45 {:.2} {:.2})"><title>{}</title></rect>"#, cx - 4.0, cy - 4.0, cx, cy, xml_escape(&title))?;
+            },
+
+            kind if kind.is_death() => {
+                writeln!(out, r#"  <text class="death" x="{:.2}" y="{:.2}" text-anchor="middle" dominant-baseline="middle" fill="firebrick">&#x2020;<title>{}</title></text>"#, cx, cy, xml_escape(&title))?;
+            },
+
+            _ => (),
+        }
+    }
+
+    write_axis(out, style, start, span, axis_y, &x)?;
+    writeln!(out, "</svg>")
+}
+
+fn draw_arrow(out: &mut dyn Write, from: (f64, f64), to: (f64, f64), id: usize) -> io::Result<()> {
+    writeln!(out, r#"  <line class="signal" id="signal-{}" x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="darkorange" stroke-dasharray="4 2" marker-end="url(#arrow)"/>"#, id, from.0, from.1, to.0, to.1)
+}
+
+fn write_axis(
+    out: &mut dyn Write,
+    style: &TimelineStyle,
+    start: Instant,
+    span: f64,
+    axis_y: f64,
+    x: &dyn Fn(Instant) -> f64,
+) -> io::Result<()> {
+    const TICKS: usize = 5;
+
+    let left = style.margin + style.label_width;
+    let right = style.width - style.margin;
+    writeln!(out, r#"  <line class="axis" x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="black"/>"#, left, axis_y, right, axis_y)?;
+
+    for tick in 0 ..= TICKS {
+        let offset = span * tick as f64 / TICKS as f64;
+        let time = start + Interval::from_f64(offset);
+        let label = report::duration_text(f64::from(time));
+
+        writeln!(out, r#"  <line x1="{0:.2}" y1="{1:.2}" x2="{0:.2}" y2="{2:.2}" stroke="black"/>"#, x(time), axis_y, axis_y + 5.0)?;
+        writeln!(out, r#"  <text x="{:.2}" y="{:.2}" text-anchor="middle">{}</text>"#, x(time), axis_y + 18.0, label)?;
+    }
+
+    Ok(())
+}
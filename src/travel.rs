@@ -0,0 +1,222 @@
+use vek::Vec3;
+
+use crate::{Position, Trajectory};
+use crate::time::{Instant, Interval};
+
+/// How a ship gets from one place to another, with accel measured in
+/// light-seconds per second per second and speed in light-seconds per second
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DriveProfile {
+    /// Accelerate for half the trip, then flip and decelerate
+    ThrustBrake {
+        accel: f64,
+    },
+
+    /// Like ThrustBrake, but stop accelerating once max_speed is reached
+    ThrustCoast {
+        accel: f64,
+        max_speed: f64,
+    },
+}
+
+impl DriveProfile {
+    pub fn accel(&self) -> f64 {
+        match *self {
+            DriveProfile::ThrustBrake { accel } => accel,
+            DriveProfile::ThrustCoast { accel, .. } => accel,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let accel = self.accel();
+
+        let max_speed = match *self {
+            DriveProfile::ThrustBrake { .. } => f64::INFINITY,
+            DriveProfile::ThrustCoast { max_speed, .. } => max_speed,
+        };
+
+        accel.is_finite() && accel > 0.0 && max_speed > 0.0
+    }
+
+    /// Top speed reached on a trip of the given length
+    pub(crate) fn peak_speed(&self, distance: f64) -> f64 {
+        let thrust_brake_peak = (distance * self.accel()).sqrt();
+
+        match *self {
+            DriveProfile::ThrustBrake { .. } => thrust_brake_peak,
+            DriveProfile::ThrustCoast { max_speed, .. } => thrust_brake_peak.min(max_speed),
+        }
+    }
+
+    /// Time taken to cover the given distance, starting and ending at rest
+    pub fn travel_time(&self, distance: f64) -> Interval {
+        Interval::from_f64(self.travel_secs(distance))
+    }
+
+    fn travel_secs(&self, distance: f64) -> f64 {
+        if distance <= 0.0 {
+            return 0.0;
+        }
+
+        let accel = self.accel();
+        let peak = self.peak_speed(distance);
+        let burn_time = peak / accel;
+        let burn_distance = peak * burn_time;
+        let coast_time = (distance - burn_distance).max(0.0) / peak;
+
+        2.0 * burn_time + coast_time
+    }
+
+    /// Distance covered and current speed, `elapsed` seconds into the trip
+    fn progress(&self, distance: f64, elapsed: f64) -> (f64, f64) {
+        let total = self.travel_secs(distance);
+
+        if elapsed <= 0.0 {
+            return (0.0, 0.0);
+        } else if elapsed >= total {
+            return (distance, 0.0);
+        }
+
+        let accel = self.accel();
+        let peak = self.peak_speed(distance);
+        let burn_time = peak / accel;
+
+        if elapsed < burn_time {
+            (0.5 * accel * elapsed.powi(2), accel * elapsed)
+        } else if elapsed < total - burn_time {
+            (0.5 * peak * burn_time + peak * (elapsed - burn_time), peak)
+        } else {
+            let remaining = total - elapsed;
+            (distance - 0.5 * accel * remaining.powi(2), accel * remaining)
+        }
+    }
+}
+
+impl Trajectory {
+    pub(crate) fn transfer_position(
+        start_place: Position,
+        start_time: Instant,
+        end_place: Position,
+        profile: DriveProfile,
+        time: Instant,
+    ) -> Position {
+        let (direction, distance) = heading(start_place, end_place);
+        let elapsed = f64::from(start_time.delta(time));
+        let (covered, _) = profile.progress(distance, elapsed);
+        start_place.offset(direction * covered)
+    }
+
+    pub(crate) fn transfer_velocity(
+        start_place: Position,
+        start_time: Instant,
+        end_place: Position,
+        profile: DriveProfile,
+        time: Instant,
+    ) -> Vec3<f64> {
+        let (direction, distance) = heading(start_place, end_place);
+        let elapsed = f64::from(start_time.delta(time));
+        let (_, speed) = profile.progress(distance, elapsed);
+        direction * speed
+    }
+
+    /// When each stretch of steady acceleration begins, and the acceleration
+    /// held until the next one
+    pub(crate) fn phases(&self) -> Vec<(f64, Vec3<f64>)> {
+        match *self {
+            Trajectory::Fixed { .. } => vec![(f64::NEG_INFINITY, Vec3::zero())],
+
+            Trajectory::Linear { accel, .. } => vec![(f64::NEG_INFINITY, accel)],
+
+            Trajectory::Transfer { start_place, start_time, end_place, profile } => {
+                let (direction, distance) = heading(start_place, end_place);
+                let start = f64::from(start_time);
+                let total = profile.travel_secs(distance);
+                let burn = profile.peak_speed(distance) / profile.accel();
+                let thrust = direction * profile.accel();
+
+                vec![
+                    (f64::NEG_INFINITY, Vec3::zero()),
+                    (start, thrust),
+                    (start + burn, Vec3::zero()),
+                    (start + total - burn, -thrust),
+                    (start + total, Vec3::zero()),
+                ]
+            },
+        }
+    }
+}
+
+fn heading(from: Position, to: Position) -> (Vec3<f64>, f64) {
+    let delta = to.0 - from.0;
+    let distance = delta.magnitude();
+
+    if distance > 0.0 {
+        (delta / distance, distance)
+    } else {
+        (Vec3::zero(), 0.0)
+    }
+}
+
+/// Plot a course from `start_place` that meets `target` wherever it will be
+/// on arrival. Returns the arrival time and the trajectory to follow, or None
+/// if the target is outrunning the drive.
+pub fn plan_intercept(
+    start_place: Position,
+    now: Instant,
+    target: &Trajectory,
+    profile: DriveProfile,
+) -> Option<(Instant, Trajectory)> {
+    const MAX_DOUBLINGS: usize = 128;
+    const BISECTIONS: usize = 200;
+
+    if !profile.is_valid() {
+        return None;
+    }
+
+    // Positive while the target is still out of reach at time t
+    let shortfall = |t: f64| -> f64 {
+        let when = now + Interval::from_f64(t);
+        let (_, distance) = heading(start_place, target.sample_at(when));
+        profile.travel_secs(distance) - t
+    };
+
+    let mut lo = 0.0;
+    let mut hi = profile.travel_secs(heading(start_place, target.sample_at(now)).1).max(1.0);
+
+    let mut doublings = 0;
+    while shortfall(hi) > 0.0 {
+        lo = hi;
+        hi *= 2.0;
+        doublings += 1;
+
+        if doublings > MAX_DOUBLINGS || !hi.is_finite() {
+            return None;
+        }
+    }
+
+    for _ in 0 .. BISECTIONS {
+        let mid = 0.5 * (lo + hi);
+        if mid <= lo || mid >= hi {
+            break;
+        }
+
+        if shortfall(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let end_place = target.sample_at(now + Interval::from_f64(hi));
+    let (_, distance) = heading(start_place, end_place);
+    let arrival = now + profile.travel_time(distance);
+
+    let trajectory = Trajectory::Transfer {
+        start_place,
+        start_time: now,
+        end_place,
+        profile,
+    };
+
+    Some((arrival, trajectory))
+}
@@ -0,0 +1,87 @@
+use histrion::history::EventKind;
+use histrion::report;
+use histrion::saga;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn annotations_reach_events() {
+    let workspace = run(r#"
+        spawn Earth
+        as Earth do
+            chapter 1
+            transmit #hello -- "first words" #contact
+            wait 1hr -- "the long silence"
+            chapter "Reply"
+            transmit #again -- #contact
+        done
+        wait 2hr -- chapter meanwhile
+        halt
+    "#);
+
+    let history = workspace.history();
+
+    let tagged = history.tagged("contact").map(|(_, event)| match &event.kind {
+        EventKind::Transmitted { signal } => signal.head.to_string(),
+        other => panic!("unexpected event {}", other),
+    }).collect::<Vec<_>>();
+    assert_eq!(tagged, vec!["hello", "again"]);
+
+    let chapters = history.chapters().iter().map(|chapter| chapter.to_string()).collect::<Vec<_>>();
+    assert_eq!(chapters, vec!["1", "meanwhile", "Reply"]);
+
+    let mut outline = Vec::new();
+    report::write_outline(&workspace, &mut outline).unwrap();
+    assert_eq!(String::from_utf8(outline).unwrap(), "
+## Chapter 1
+
+- 0sec, Earth: first words
+- 0sec, Earth: the long silence
+");
+}
+
+#[test]
+fn annotations_end_with_the_line() {
+    let script = saga::parse(r#"
+        wait 1s -- "one" #a
+        wait 2s
+    "#).unwrap();
+
+    let body = script.into_inner();
+    assert_eq!(format!("{}", body[0]), r#"wait 1sec -- "one" #a"#);
+    assert_eq!(format!("{}", body[1]), "wait 2sec");
+
+    assert!(saga::parse("wait 1s -- \"one\" \"two\"").is_err());
+}
+
+#[test]
+fn annotated_blocks_print_and_parse_again() {
+    let src = r#"
+        spawn Earth
+        as Earth do -- "a \"quiet\" start" #opening
+            chapter 1
+            wait 1hr
+        done
+        every 1hr until 2hr do -- #tick
+            trace 1
+        done
+        def greet() do
+            trace 2
+        done -- "hello"
+        halt
+    "#;
+
+    let printed = saga::parse(src).unwrap().pretty_print();
+    assert!(printed.contains(r#"as Earth do -- "a \"quiet\" start" #opening"#));
+    assert!(printed.contains("    wait 3600sec -- chapter \"1\"\n"));
+    assert!(printed.contains("every 3600sec until 7200sec do -- #tick\n"));
+    assert!(printed.contains("def greet() do -- \"hello\"\n"));
+
+    let reparsed = saga::parse(&printed).unwrap();
+    assert_eq!(reparsed.pretty_print(), printed);
+
+    // The chapter set inside `as` ends with the block
+    assert!(printed.contains("\nhalt\n"));
+}
@@ -0,0 +1,48 @@
+use histrion::{Error, Workspace};
+use histrion::saga;
+
+#[test]
+fn errors_say_where_the_fiber_was() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    let script = saga::parse("
+        def steer() do
+            self.accel = (1, 0, 0)
+        done
+        def launch() do
+            call steer()
+        done
+        spawn Ship
+        spawn Shuttle
+        as Shuttle do
+            board Ship
+        done
+        wait 5s
+        as Shuttle do
+            call launch()
+        done
+    ").unwrap();
+
+    workspace.perform(script.into_inner()).unwrap();
+    let err = match workspace.simulate().unwrap_err() {
+        err @ Error::InFiber { .. } => err,
+        other => panic!("expected a backtrace, got {:?}", other),
+    };
+
+    assert!(matches!(err.root(), Error::Aboard { .. }));
+
+    let backtrace = err.backtrace().unwrap();
+    assert_eq!(backtrace.actor, workspace.lookup("Shuttle").unwrap());
+    assert_eq!(backtrace.actor_name.as_deref(), Some("Shuttle"));
+    assert_eq!(f64::from(backtrace.time), 5.0);
+
+    let methods = backtrace.frames.iter().map(|frame| frame.method.as_deref()).collect::<Vec<_>>();
+    assert_eq!(methods, vec![Some("steer"), Some("launch"), None]);
+    assert_eq!(backtrace.frames[0].pc, 0);
+    assert_eq!(backtrace.frames[1].pc, 0);
+
+    let text = err.to_string();
+    assert!(text.contains("in fiber"), "{}", text);
+    assert!(text.contains("method launch, action 0: call steer()"), "{}", text);
+}
@@ -0,0 +1,121 @@
+use histrion::{Error, Workspace};
+use histrion::saga;
+use histrion::time::{Instant, Interval};
+
+fn perform(workspace: &mut Workspace, src: &str) -> Result<(), Error> {
+    workspace.perform(saga::parse(src).unwrap().into_inner())
+}
+
+#[test]
+fn crew_goes_where_the_ship_goes() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    perform(&mut workspace, "
+        spawn Ship
+        spawn Crew
+        as Crew do
+            board Ship
+        done
+        as Ship do
+            self.accel = (1, 0, 0)
+        done
+        wait 2s
+        as Crew do
+            disembark
+        done
+        wait 2s
+        halt
+    ").unwrap();
+    workspace.simulate().unwrap();
+
+    let ship = workspace.lookup("Ship").unwrap();
+    let crew = workspace.lookup("Crew").unwrap();
+
+    // Aboard for two seconds at 1 ls/s², then drifting at 2 ls/s
+    let place = workspace.history().place_of(crew, Instant::default() + Interval::from_f64(4.0));
+    assert_eq!(place.0.x, 2.0 + 4.0);
+
+    let ship_place = workspace.history().place_of(ship, Instant::default() + Interval::from_f64(4.0));
+    assert_eq!(ship_place.0.x, 8.0);
+
+    assert_eq!(workspace.parent_of(crew), None);
+}
+
+#[test]
+fn passengers_cannot_steer_or_board_themselves() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    perform(&mut workspace, "
+        spawn Ship
+        spawn Shuttle
+        as Shuttle do
+            board Ship
+        done
+    ").unwrap();
+
+    let ship = workspace.lookup("Ship").unwrap();
+    let shuttle = workspace.lookup("Shuttle").unwrap();
+    assert_eq!(workspace.parent_of(shuttle), Some(ship));
+    assert_eq!(workspace.passengers_of(ship), vec![shuttle]);
+
+    let steer = perform(&mut workspace, "as Shuttle do self.accel = (1, 0, 0) done");
+    assert!(matches!(steer.unwrap_err().root(), Error::Aboard { .. }));
+
+    let loop_back = perform(&mut workspace, "as Ship do board Shuttle done");
+    assert!(matches!(loop_back.unwrap_err().root(), Error::CannotBoard { .. }));
+}
+
+#[test]
+fn boarding_needs_to_be_alongside() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    perform(&mut workspace, "
+        spawn Ship
+        spawn Crew
+        as Ship do
+            self.accel = (1, 0, 0)
+        done
+        wait 2s
+    ").unwrap();
+    workspace.simulate().unwrap();
+
+    let board = perform(&mut workspace, "as Crew do board Ship done");
+    assert!(matches!(board.unwrap_err().root(), Error::OutOfReach { .. }));
+
+    let crew = workspace.lookup("Crew").unwrap();
+    assert_eq!(workspace.parent_of(crew), None);
+}
+
+#[test]
+fn passengers_are_set_down_when_the_ship_dies() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    perform(&mut workspace, "
+        spawn Ship
+        spawn Crew
+        as Crew do
+            board Ship
+        done
+        as Ship do
+            self.accel = (1, 0, 0)
+            wait 2s
+            die
+        done
+        wait 4s
+        halt
+    ").unwrap();
+    workspace.simulate().unwrap();
+
+    let ship = workspace.lookup("Ship").unwrap();
+    let crew = workspace.lookup("Crew").unwrap();
+    assert_eq!(workspace.parent_of(crew), None);
+    assert!(workspace.passengers_of(ship).is_empty());
+
+    // Set down at 2 ls after two seconds, then drifting at 2 ls/s
+    let place = workspace.history().place_of(crew, Instant::default() + Interval::from_f64(4.0));
+    assert_eq!(place.0.x, 2.0 + 4.0);
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use histrion::Workspace;
+use histrion::action::*;
+use histrion::builtins::Builtin;
+use histrion::time::Interval;
+
+fn call(func: Builtin, args: Vec<Expr>) -> Expr {
+    Expr::Builtin { func, args: args.into() }
+}
+
+fn var(name: &str) -> Expr {
+    Expr::Var { name: name.into() }
+}
+
+fn num(value: &Value) -> f64 {
+    match value {
+        Value::Num(n) => n.into_inner(),
+        other => panic!("expected a number, got {}", other),
+    }
+}
+
+fn field(value: &Value, name: &str) -> f64 {
+    match value {
+        Value::Struct(dict) => num(&dict[name]),
+        other => panic!("expected a struct, got {}", other),
+    }
+}
+
+#[test]
+fn spatial_functions() {
+    let script: Arc<[Action]> = vec![
+        Action::Spawn { name: "Earth".into() },
+        Action::Spawn { name: "Probe".into() },
+
+        Action::AsActor {
+            name: "Probe".into(),
+            script: vec![
+                Action::SetAccel { value: (2.0, 0.0, 0.0).into() },
+                Action::Wait { interval: Interval::from_f64(10.0) },
+                Action::SetAccel { value: (-2.0, 0.0, 0.0).into() },
+                Action::Wait { interval: Interval::from_f64(10.0) },
+            ].into(),
+        },
+
+        Action::Wait { interval: Interval::from_f64(20.0) },
+        Action::Halt,
+    ].into();
+
+    let mut workspace = Workspace::new();
+    workspace.perform(script).unwrap();
+    workspace.simulate().unwrap();
+
+    let earth_to_probe = vec![var("Earth"), var("Probe")];
+
+    let distance = workspace.evaluate(&call(Builtin::Distance, earth_to_probe.clone())).unwrap();
+    assert!((num(&distance) - 200.0).abs() < 1e-9);
+
+    let delay = workspace.evaluate(&call(Builtin::LightDelay, earth_to_probe.clone())).unwrap();
+    assert!((num(&delay) - 200.0).abs() < 1e-9);
+
+    let bearing = workspace.evaluate(&call(Builtin::Bearing, earth_to_probe.clone())).unwrap();
+    assert!((field(&bearing, "x") - 1.0).abs() < 1e-9);
+
+    let mut travel = earth_to_probe;
+    travel.push(Expr::NumConst { value: 2.0 });
+    let travel_time = workspace.evaluate(&call(Builtin::TravelTime, travel)).unwrap();
+    assert!((num(&travel_time) - 20.0).abs() < 1e-9);
+}
+
+#[test]
+fn builtins_accept_position_structs() {
+    let mut workspace = Workspace::new();
+
+    let here = Expr::Field {
+        subject: Expr::Myself.into(),
+        field_name: "position".into(),
+    };
+
+    let expr = call(Builtin::Distance, vec![here.clone(), here]);
+    assert_eq!(num(&workspace.evaluate(&expr).unwrap()), 0.0);
+
+    let expr = call(Builtin::Distance, vec![Expr::NumConst { value: 1.0 }, Expr::Myself]);
+    assert!(workspace.evaluate(&expr).is_err());
+}
+
+#[test]
+fn relative_velocity_of_accelerating_actors() {
+    let script: Arc<[Action]> = vec![
+        Action::Spawn { name: "Scout".into() },
+        Action::Spawn { name: "Tender".into() },
+
+        Action::AsActor {
+            name: "Scout".into(),
+            script: vec![Action::SetAccel { value: (1.0, 0.0, 0.0).into() }].into(),
+        },
+
+        Action::AsActor {
+            name: "Tender".into(),
+            script: vec![Action::SetAccel { value: (0.0, 2.0, 0.0).into() }].into(),
+        },
+
+        Action::Wait { interval: Interval::from_f64(5.0) },
+        Action::Halt,
+    ].into();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script).unwrap();
+    workspace.simulate().unwrap();
+    assert_eq!(f64::from(workspace.now()), 5.0);
+
+    // After 5s the scout moves at (5, 0, 0) and the tender at (0, 10, 0)
+    let expr = call(Builtin::RelativeVelocity, vec![var("Scout"), var("Tender")]);
+    let velocity = workspace.evaluate(&expr).unwrap();
+    assert!((field(&velocity, "x") + 5.0).abs() < 1e-9);
+    assert!((field(&velocity, "y") - 10.0).abs() < 1e-9);
+    assert_eq!(field(&velocity, "z"), 0.0);
+}
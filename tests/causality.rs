@@ -0,0 +1,39 @@
+use histrion::causality::{self, Cause};
+use histrion::history::EventKind;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn links_signals_spawns_and_fibers() {
+    let workspace = run("
+        spawn Mars
+        as Mars do
+            wait 1hr
+            transmit #arrived(Mars)
+        done
+        listen #arrived(Mars)
+        halt
+    ");
+
+    let history = workspace.history();
+    let edges = history.causal_edges();
+    let kind = |id: usize| &history.events()[id].kind;
+
+    let signal = edges.iter().find(|edge| edge.cause == Cause::Signal).unwrap();
+    assert!(matches!(kind(signal.from), EventKind::Transmitted { .. }));
+    assert!(matches!(kind(signal.to), EventKind::Received { .. }));
+
+    let spawn = edges.iter().find(|edge| edge.cause == Cause::Spawn).unwrap();
+    assert!(matches!(kind(spawn.from), EventKind::Spawned { .. }));
+    assert_eq!(history.events()[spawn.to].actor, workspace.lookup("Mars").unwrap());
+
+    assert_eq!(edges.iter().filter(|edge| edge.cause == Cause::Delegate).count(), 1);
+
+    let mut dot = Vec::new();
+    causality::write_dot(&workspace, &mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph causality {"));
+    assert!(dot.contains(&format!("e{} -> e{} [style=dashed", signal.from, signal.to)));
+}
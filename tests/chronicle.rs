@@ -0,0 +1,74 @@
+use histrion::chronicle;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn chronicle_groups_by_day_and_actor() {
+    let workspace = run(r#"
+        spawn Earth
+        spawn Mars
+        as Mars do
+            listen #arrived(Mars)
+            wait 1hr -- "Mars settles in"
+        done
+        wait 1day
+        wait 1hr
+        as Earth do
+            transmit #arrived(Mars)
+        done
+        wait 2hr
+        halt
+    "#);
+
+    let mut out = Vec::new();
+    chronicle::write_markdown(&workspace, &mut out).unwrap();
+
+    assert_eq!(String::from_utf8(out).unwrap(), "\
+# Chronicle
+
+## Year 1, day 1
+
+### Everything
+
+- 00:00:00 spawned Earth
+- 00:00:00 spawned Mars
+
+## Year 1, day 2
+
+### Earth
+
+- 01:00:00 transmitted #arrived(Mars); Mars received it 0sec later
+
+### Mars
+
+- 01:00:00 received #arrived(Mars) from Earth, sent 0sec earlier
+- 01:00:00 wait 3600sec: *Mars settles in*
+
+### Everything
+
+- 03:00:00 brought the story to an end
+");
+}
+
+#[test]
+fn chronicle_days_start_at_midnight_in_later_years() {
+    let workspace = run(r#"
+        spawn Earth
+        wait 400day
+        wait 2hr
+        halt
+    "#);
+
+    let mut out = Vec::new();
+    chronicle::write_markdown(&workspace, &mut out).unwrap();
+
+    assert!(String::from_utf8(out).unwrap().ends_with("\
+## Year 2, day 36
+
+### Everything
+
+- 02:00:00 brought the story to an end
+"));
+}
@@ -0,0 +1,43 @@
+use std::process::Command;
+
+fn run_saga(name: &str, src: &str, args: &[&str]) -> std::process::Output {
+    let path = std::env::temp_dir().join(format!("histrion-cli-{}.saga", name));
+    std::fs::write(&path, src).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_histrion"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn prints_traces() {
+    let output = run_saga("traces", "x = 2\ntrace x\n", &["--traces"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("trace x = 2"));
+}
+
+#[test]
+fn fails_on_runtime_error() {
+    let output = run_saga("error", "as Nobody do\n    halt\ndone\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn runs_reject_single_run_options() {
+    let output = run_saga("runs-timeline", "halt\n", &["--runs", "2", "--timeline", "out.svg"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--timeline cannot be used with --runs"));
+
+    let output = run_saga("runs-interactive", "halt\n", &["--runs", "2", "--interactive"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn unknown_ring_actor_is_a_usage_error() {
+    let map = std::env::temp_dir().join("histrion-cli-rings.svg");
+    let output = run_saga("rings", "spawn Earth\nhalt\n", &["--map", map.to_str().unwrap(), "--rings", "Eatrh"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no actor named Eatrh"));
+}
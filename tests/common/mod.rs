@@ -0,0 +1,11 @@
+use histrion::Workspace;
+use histrion::saga;
+
+/// Parse and perform the saga quietly, then simulate it to the end
+pub fn run(src: &str) -> Workspace {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(saga::parse(src).unwrap().into_inner()).unwrap();
+    workspace.simulate().unwrap();
+    workspace
+}
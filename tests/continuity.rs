@@ -0,0 +1,68 @@
+use histrion::continuity::Inconsistency;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn consistent_saga_passes() {
+    let workspace = run("
+        spawn Earth
+        spawn Relay
+        as Relay do
+            listen #ping
+        done
+        as Earth do
+            transmit #ping
+        done
+        wait 1s
+        halt
+    ");
+
+    assert_eq!(workspace.check_continuity(), vec![]);
+}
+
+#[test]
+fn impossible_events_are_flagged() {
+    let workspace = run("
+        spawn Earth
+        spawn Ghost
+        spawn Probe
+        as Ghost do
+            die
+            trace 1
+        done
+        as Probe do
+            self.accel = (1, 0, 0)
+            wait 5s
+            listen #ping
+        done
+        wait 10s
+        as Earth do
+            transmit #ping
+        done
+        wait 1s
+        halt
+    ");
+
+    let problems = workspace.check_continuity();
+    let ghost = workspace.lookup("Ghost").unwrap();
+    let probe = workspace.lookup("Probe").unwrap();
+
+    assert!(problems.iter().any(|problem| match *problem {
+        Inconsistency::ActedWhileDead { event, .. } => workspace.history().events()[event].actor == ghost,
+        _ => false,
+    }));
+
+    assert!(problems.iter().any(|problem| match *problem {
+        Inconsistency::FasterThanLight { reception, light_delay, .. } => {
+            workspace.history().events()[reception].actor == probe && light_delay > 0.0
+        },
+        _ => false,
+    }));
+
+    assert!(problems.iter().any(|problem| match *problem {
+        Inconsistency::Superluminal { actor, speed, .. } => actor == probe && speed > 1.0,
+        _ => false,
+    }));
+}
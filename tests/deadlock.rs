@@ -0,0 +1,47 @@
+use histrion::Workspace;
+use histrion::saga;
+
+#[test]
+fn lost_listeners_are_reported() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+
+    let script = saga::parse("
+        spawn Earth
+        spawn Mars
+        as Earth do
+            listen #hello(Mars)
+        done
+        as Mars do
+            wait 1hr
+            listen #never
+        done
+        def greet() do
+            transmit #hello(Mars)
+        done
+    ").unwrap();
+
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    assert!(workspace.ran_dry());
+
+    let lost = workspace.lost_listeners();
+    assert_eq!(lost.len(), 2);
+
+    let earth = workspace.lookup("Earth").unwrap();
+    let mars = workspace.lookup("Mars").unwrap();
+
+    assert_eq!(lost[0].actor, earth);
+    assert_eq!(lost[0].signal.head.as_ref(), "hello");
+    assert!(lost[0].has_transmitter);
+
+    assert_eq!(lost[1].actor, mars);
+    assert_eq!(lost[1].signal.head.as_ref(), "never");
+    assert!(!lost[1].has_transmitter);
+    assert_eq!(lost[1].since.map(f64::from), Some(3600.0));
+
+    // Queueing more work means it hasn't run dry after all
+    workspace.perform(saga::parse("as Earth do wait 1s done").unwrap().into_inner()).unwrap();
+    assert!(!workspace.ran_dry());
+}
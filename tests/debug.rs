@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use histrion::Workspace;
+use histrion::action::*;
+use histrion::debug::*;
+use histrion::script::Script;
+use histrion::time::Instant;
+
+fn var(name: &str) -> Expr {
+    Expr::Var { name: name.into() }
+}
+
+#[test]
+fn break_inside_method_and_watch_locals() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.add_breakpoint(Breakpoint::Action { keyword: "trace".into() });
+    workspace.add_watch(var("foo"));
+
+    workspace.perform(Script::default().into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let pause = workspace.paused().unwrap().clone();
+    assert_eq!(f64::from(pause.time), 1800.0);
+    assert_eq!(pause.actor, workspace.lookup("Mars").unwrap());
+    assert_eq!(pause.watches[0].1.as_ref().unwrap(), &Value::Num(2.0.into()));
+
+    let stack = workspace.call_stack();
+    assert_eq!(stack.last().unwrap().method.as_deref(), Some("trace_foo"));
+
+    // The second trace is back in Mars's own script, where foo is unbound
+    workspace.resume().unwrap();
+    let pause = workspace.paused().unwrap();
+    assert_eq!(f64::from(pause.time), 3600.0);
+    assert!(pause.watches[0].1.is_err());
+
+    workspace.resume().unwrap();
+    assert!(workspace.has_halted());
+    assert!(!workspace.is_paused());
+}
+
+#[test]
+fn single_step() {
+    let script: Arc<[Action]> = vec![
+        Action::WriteLocal { name: "x".into(), value: Expr::NumConst { value: 1.0 }.into() },
+        Action::WriteLocal { name: "x".into(), value: Expr::NumConst { value: 2.0 }.into() },
+        Action::Trace { expr: var("x").into() },
+    ].into();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.add_breakpoint(Breakpoint::Instant { at: Instant::default() });
+
+    workspace.perform(script).unwrap();
+    assert_eq!(workspace.paused().unwrap().action.as_ref().unwrap().keyword(), "assign");
+
+    workspace.step().unwrap();
+    assert_eq!(workspace.inspect(&var("x")).unwrap(), Value::Num(1.0.into()));
+    assert_eq!(workspace.paused().unwrap().reason, PauseReason::Step);
+
+    workspace.step().unwrap();
+    assert_eq!(workspace.inspect(&var("x")).unwrap(), Value::Num(2.0.into()));
+    assert_eq!(workspace.paused().unwrap().action.as_ref().unwrap().keyword(), "trace");
+
+    workspace.resume().unwrap();
+    assert!(workspace.has_halted());
+}
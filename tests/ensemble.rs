@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use histrion::action::Action;
+use histrion::ensemble::{Ensemble, Histogram};
+use histrion::saga;
+
+const ERRAND: &str = "
+    spawn Near
+    spawn Far
+    spawn Ship
+    as Far do
+        self.accel = (1, 0, 0)
+        wait 1s
+        self.accel = (-1, 0, 0)
+        wait 1s
+        self.accel = (0, 0, 0)
+    done
+    wait 3s
+    as Ship do
+        travel to choice(Near, Far) by thrust-brake at 1 c/sec
+        transmit #arrived
+        die
+    done
+    listen #arrived
+    halt
+";
+
+fn scripts() -> Vec<Arc<[Action]>> {
+    vec![saga::parse(ERRAND).unwrap().into_inner()]
+}
+
+#[test]
+fn aggregates_outcomes_across_seeds() {
+    let ensemble = Ensemble {
+        runs: 40,
+        first_seed: 1,
+        threads: 4,
+        until: None,
+    };
+
+    let report = ensemble.run(&scripts());
+
+    assert_eq!(report.outcomes.len(), 40);
+    assert_eq!(report.failures(), 0);
+    assert!(report.outcomes.iter().enumerate().all(|(i, outcome)| outcome.seed == 1 + i as u64));
+
+    assert_eq!(report.death_probability("Ship"), 1.0);
+    assert_eq!(report.death_probability("Near"), 0.0);
+
+    let halts = report.halt_times();
+    assert_eq!(halts.len(), 40);
+    assert_eq!(halts[0], 3.0);
+    assert_eq!(halts[39], 5.0);
+
+    let arrivals = report.first_receptions("arrived");
+    let histogram = Histogram::of(&arrivals, 2);
+    assert_eq!(histogram.counts.iter().sum::<usize>(), 40);
+    assert!(histogram.counts.iter().all(|&count| count > 0));
+
+    // The same seeds give the same story, whatever the threading
+    let again = Ensemble { threads: 1, ..ensemble }.run(&scripts());
+    assert_eq!(again.halt_times(), halts);
+}
+
+#[test]
+fn seeds_wrap_around() {
+    let ensemble = Ensemble {
+        runs: 2,
+        first_seed: u64::MAX,
+        threads: 1,
+        until: None,
+    };
+
+    let seeds = ensemble.run(&scripts()).outcomes.iter().map(|outcome| outcome.seed).collect::<Vec<_>>();
+    assert_eq!(seeds, vec![u64::MAX, 0]);
+}
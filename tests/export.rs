@@ -0,0 +1,130 @@
+use histrion::Trajectory;
+use histrion::export::{self, SampleFormat, Sampling};
+use histrion::history::Segment;
+use histrion::time::{Instant, Interval};
+use histrion::travel::DriveProfile;
+
+mod common;
+
+use common::run;
+
+const FLIGHT: &str = "
+    spawn Earth
+    spawn Probe
+    as Probe do
+        self.accel = (0.5, 0, 0)
+        wait 10s
+        die
+    done
+    wait 20s
+    halt
+";
+
+#[test]
+fn samples_follow_trajectories() {
+    let workspace = run(FLIGHT);
+    let probe = workspace.lookup("Probe").unwrap();
+
+    let sampling = Sampling {
+        from: Instant::default(),
+        until: Instant::default() + Interval::from_f64(20.0),
+        step: Interval::from_f64(4.0),
+    };
+
+    let samples = export::sample(&workspace, &sampling);
+    let probe_samples = samples.iter().filter(|sample| sample.actor == probe).collect::<Vec<_>>();
+
+    assert_eq!(probe_samples.len(), 6);
+    assert_eq!(probe_samples[2].position.0.x, 0.25 * 8.0f64.powi(2));
+    assert_eq!(probe_samples[2].velocity.x, 4.0);
+    assert!(probe_samples[2].alive);
+    assert!(!probe_samples[3].alive);
+
+    let mut csv = Vec::new();
+    export::write_samples(&workspace, &sampling, SampleFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+
+    assert!(csv.starts_with("time,actor,x,y,z,vx,vy,vz,alive\n"));
+    assert!(csv.contains("\n8,Probe,16,0,0,4,0,0,true\n"));
+}
+
+#[test]
+fn segments_round_trip_exactly() {
+    let workspace = run("
+        spawn Probe
+        spawn Beacon
+        as Beacon do
+            self.accel = (0, 0.05, 0)
+            wait 1s
+            self.accel = (0, 0, 0)
+        done
+        as Probe do
+            wait 1s
+            self.accel = (0.1, 0.2, 0.3)
+            wait 0.7s
+            travel to Beacon by thrust-coast at 0.1 c/sec up to 0.3 c
+        done
+        wait 2s
+        halt
+    ");
+
+    let mut json = Vec::new();
+    export::write_segments(&workspace, &mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+
+    assert!(json.contains("\"accel\": [0.1, 0.2, 0.3]"));
+    assert!(json.contains("\"kind\": \"thrust-coast\""));
+
+    let actors = export::read_segments(&json).unwrap();
+    assert_eq!(actors.len(), 2);
+
+    for actor in actors {
+        let id = workspace.lookup(&actor.actor).unwrap();
+        let segments = workspace.history().segments_of(id);
+
+        assert_eq!(actor.created, workspace.creation_date_of(id));
+        assert_eq!(actor.died, None);
+        assert_eq!(actor.segments, segments);
+
+        // Equal as f64s lets 0.0 and -0.0 through, so compare the bits too
+        assert_eq!(actor.segments.iter().map(bits).collect::<Vec<_>>(), segments.iter().map(bits).collect::<Vec<_>>());
+    }
+
+    assert!(export::read_segments("[{\"actor\": \"Probe\"}]").is_err());
+}
+
+fn bits(segment: &Segment) -> Vec<u64> {
+    let mut numbers = vec![f64::from(segment.since)];
+
+    match segment.trajectory {
+        Trajectory::Fixed { value } => numbers.extend(value.0.into_array()),
+
+        Trajectory::Linear { start_place, start_time, start_velocity, accel } => {
+            numbers.extend(start_place.0.into_array());
+            numbers.push(f64::from(start_time));
+            numbers.extend(start_velocity.into_array());
+            numbers.extend(accel.into_array());
+        },
+
+        Trajectory::Transfer { start_place, start_time, end_place, profile } => {
+            numbers.extend(start_place.0.into_array());
+            numbers.push(f64::from(start_time));
+            numbers.extend(end_place.0.into_array());
+
+            match profile {
+                DriveProfile::ThrustBrake { accel } => numbers.push(accel),
+                DriveProfile::ThrustCoast { accel, max_speed } => numbers.extend([accel, max_speed]),
+            }
+        },
+    }
+
+    numbers.into_iter().map(f64::to_bits).collect()
+}
+
+#[test]
+fn non_finite_numbers_are_not_json() {
+    for number in ["NaN", "inf", "-inf", "1e400"] {
+        let src = format!(r#"[{{"actor": "A", "created": {}, "died": null, "segments": []}}]"#, number);
+        assert!(export::read_segments(&src).is_err(), "{}", number);
+    }
+}
@@ -0,0 +1,154 @@
+use histrion::{Liveness, Workspace};
+use histrion::action::Action;
+use histrion::fault::FaultPolicy;
+use histrion::history::EventKind;
+use histrion::saga;
+
+const SAGA: &str = "
+    spawn Ship
+    spawn Probe
+    as Ship do
+        wait 1s
+        trace Nowhere.position
+        trace 1
+    done
+    as Probe do
+        wait 2s
+        trace 2
+    done
+    wait 3s
+    halt
+";
+
+fn run(policy: FaultPolicy, src: &str) -> Workspace {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.set_fault_policy(policy);
+    workspace.perform(saga::parse(src).unwrap().into_inner()).unwrap();
+    workspace
+}
+
+fn failures(workspace: &Workspace) -> usize {
+    workspace.history().events().iter()
+        .filter(|event| matches!(event.kind, EventKind::Failed { .. }))
+        .count()
+}
+
+fn traces(workspace: &Workspace) -> usize {
+    workspace.history().events().iter()
+        .filter(|event| matches!(event.kind, EventKind::Traced { .. }))
+        .count()
+}
+
+#[test]
+fn abort_stops_the_run() {
+    let mut workspace = run(FaultPolicy::Abort, SAGA);
+    assert!(workspace.simulate().is_err());
+    assert!(!workspace.has_halted());
+    assert_eq!(failures(&workspace), 0);
+}
+
+#[test]
+fn isolated_failures_are_recorded_and_the_rest_carries_on() {
+    let mut workspace = run(FaultPolicy::KillFiber, SAGA);
+    workspace.simulate().unwrap();
+    assert!(workspace.has_halted());
+    assert_eq!(failures(&workspace), 1);
+
+    let ship = workspace.lookup("Ship").unwrap();
+    assert!(matches!(workspace.liveness_of(ship), Liveness::Alive));
+    assert_eq!(traces(&workspace), 1);
+
+    let mut workspace = run(FaultPolicy::KillActor, "
+        spawn Ship
+        as Ship do
+            every 2s do
+                trace 1
+            done
+        done
+        as Ship do
+            listen #go()
+            trace Nowhere.position
+        done
+        wait 3s
+        transmit #go()
+        wait 2s
+        halt
+    ");
+    workspace.simulate().unwrap();
+
+    let ship = workspace.lookup("Ship").unwrap();
+    assert!(matches!(workspace.liveness_of(ship), Liveness::Dead));
+    assert_eq!(workspace.next_task_of(ship), None);
+
+    // The recurring trace ran at 0s and 2s, before the failure, but not at 4s
+    assert_eq!(traces(&workspace), 2);
+}
+
+#[test]
+fn the_supervisor_hears_of_failures() {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.set_fault_policy(FaultPolicy::KillFiber);
+    workspace.set_fault_supervisor(Some(workspace.supervisor()));
+
+    workspace.perform(saga::parse("
+        spawn Ship
+        as Ship do
+            wait 1s
+            trace Nowhere.position
+        done
+        listen #fault(Ship)
+        trace 42
+        halt
+    ").unwrap().into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    assert!(workspace.has_halted());
+    assert_eq!(f64::from(workspace.now()), 1.0);
+    assert_eq!(traces(&workspace), 1);
+}
+
+#[test]
+fn killed_actors_are_marked_as_such() {
+    let mut workspace = run(FaultPolicy::KillActor, SAGA);
+    workspace.simulate().unwrap();
+
+    let events = workspace.history().events();
+    let failure = events.iter().position(|event| matches!(event.kind, EventKind::Failed { .. })).unwrap();
+    let killed = events.iter().find(|event| matches!(event.kind, EventKind::Killed { .. })).unwrap();
+
+    assert!(matches!(killed.kind, EventKind::Killed { failure: id } if id == failure));
+    assert!(killed.kind.is_death());
+    assert!(!events.iter().any(|event| matches!(event.kind, EventKind::Performed { action: Action::Die })));
+}
+
+#[test]
+fn the_dead_have_nothing_left_to_do() {
+    let mut workspace = run(FaultPolicy::Abort, "
+        spawn Ship
+        as Ship do
+            listen #go()
+            trace 1
+        done
+        as Ship do
+            wait 5s
+            trace 2
+        done
+        wait 1s
+        as Ship do
+            die
+        done
+        wait 10s
+        transmit #go()
+        halt
+    ");
+    workspace.simulate().unwrap();
+
+    let ship = workspace.lookup("Ship").unwrap();
+    assert!(workspace.listening_for(ship).is_empty());
+    assert_eq!(workspace.next_task_of(ship), None);
+    assert_eq!(traces(&workspace), 0);
+
+    assert!(!workspace.history().events().iter().any(|event| matches!(event.kind, EventKind::Received { .. })));
+}
@@ -0,0 +1,76 @@
+use histrion::Workspace;
+use histrion::history::EventKind;
+
+mod common;
+
+use common::run;
+
+fn find(workspace: &Workspace, actor: &str, keyword: &str) -> usize {
+    let actor = workspace.lookup(actor).unwrap();
+
+    workspace.history().events().iter().position(|event| {
+        let matches = match &event.kind {
+            EventKind::Performed { action } => action.keyword() == keyword,
+            EventKind::Transmitted { .. } => keyword == "transmit",
+            EventKind::Received { .. } => keyword == "received",
+            _ => false,
+        };
+
+        event.actor == actor && matches
+    }).unwrap()
+}
+
+const PING: &str = "
+    spawn Earth
+    spawn Probe
+    as Probe do
+        self.accel = (2, 0, 0)
+        wait 10s
+        self.accel = (-2, 0, 0)
+        wait 10s
+        self.accel = (0, 0, 0)
+        listen #ping
+    done
+    wait 30s
+    as Earth do
+        transmit #ping
+    done
+    wait 1s
+    halt
+";
+
+#[test]
+fn causal_chains() {
+    let workspace = run(PING);
+    let probe = workspace.lookup("Probe").unwrap();
+
+    let ping = find(&workspace, "Earth", "transmit");
+    let heard = find(&workspace, "Probe", "received");
+
+    assert!(workspace.caused(ping, heard));
+    assert!(!workspace.caused(heard, ping));
+    assert_eq!(workspace.first_informed(probe, ping), Some(heard));
+}
+
+#[test]
+fn light_cones() {
+    let workspace = run(PING);
+    let probe = workspace.lookup("Probe").unwrap();
+
+    let ping = find(&workspace, "Earth", "transmit");
+    let departure = find(&workspace, "Probe", "accel");
+    let waiting = find(&workspace, "Everything", "wait");
+    let coasting = workspace.history().events().iter().position(|event| {
+        event.actor == probe && f64::from(event.time) == 10.0
+    }).unwrap();
+
+    // Probe set off from Earth's doorstep, so light had plenty of time
+    assert!(!workspace.caused(departure, ping));
+    assert!(workspace.could_influence(departure, ping));
+
+    // But ten seconds on it was already a hundred light-seconds away
+    assert!(!workspace.could_influence(waiting, coasting));
+
+    let earliest = workspace.earliest_knowledge(probe, ping).unwrap();
+    assert!((f64::from(earliest) - 230.0).abs() < 1e-3);
+}
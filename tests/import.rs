@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use histrion::Workspace;
+use histrion::history::EventKind;
+use histrion::import::{ImportError, Loader};
+
+fn write_sagas(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("histrion-import-{}", dir));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (name, src) in files {
+        std::fs::write(dir.join(name), src).unwrap();
+    }
+
+    dir
+}
+
+#[test]
+fn imports_are_namespaced() {
+    let dir = write_sagas("namespaced", &[
+        ("stars.saga", "
+            spawn Sol
+            def shine() do
+                trace Sol
+            done
+        "),
+        ("fleet.saga", "
+            import \"stars.saga\"
+            spawn Sol
+            as Sol do
+                call stars::shine()
+            done
+        "),
+        ("main.saga", "
+            import \"stars.saga\"
+            import \"fleet.saga\" as ships
+            call stars::shine()
+            trace ships::Sol
+            halt
+        "),
+    ]);
+
+    let script = Loader::new().load(&dir.join("main.saga")).unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let names = workspace.globals().into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+
+    // stars.saga is imported twice but loaded once
+    assert_eq!(names, vec!["Everything", "ships::Sol", "stars::Sol"]);
+
+    let traces = workspace.history().events().iter()
+        .filter(|event| matches!(event.kind, EventKind::Traced { .. }))
+        .count();
+    assert_eq!(traces, 3);
+}
+
+#[test]
+fn import_cycles_are_caught() {
+    let dir = write_sagas("cycle", &[
+        ("a.saga", "import \"b.saga\"\n"),
+        ("b.saga", "import \"a.saga\"\n"),
+    ]);
+
+    match Loader::new().load(&dir.join("a.saga")) {
+        Err(ImportError::Cycle { chain }) => assert_eq!(chain.len(), 3),
+        other => panic!("expected a cycle, got {:?}", other.map(|_| ())),
+    }
+}
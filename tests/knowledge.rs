@@ -0,0 +1,63 @@
+use histrion::Workspace;
+use histrion::action::Value;
+use histrion::history::EventKind;
+use histrion::knowledge::Fact;
+use histrion::saga;
+use histrion::time::{Instant, Interval};
+
+#[test]
+fn actors_know_what_reached_them() {
+    let script = saga::parse("
+        spawn Earth
+        spawn Mars
+        as Mars do
+            if knows #arrived(Mars) do
+                trace 0
+            done
+            listen #arrived(Mars)
+            if knows #arrived(Mars) do
+                trace 1
+            else
+                trace 0
+            done
+            if not knows #departed(Mars) do
+                trace 2
+            done
+        done
+        wait 1hr
+        as Earth do
+            transmit #arrived(Mars)
+        done
+        wait 1s
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let traces = workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Traced { value: Value::Num(n), .. } => Some(n.into_inner()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    assert_eq!(traces, vec![1.0, 2.0]);
+
+    let earth = workspace.lookup("Earth").unwrap();
+    let mars = workspace.lookup("Mars").unwrap();
+    let at = |secs: f64| Instant::default() + Interval::from_f64(secs);
+
+    assert!(workspace.knew_at(mars, at(1800.0)).is_empty());
+
+    let known = workspace.knew_at(mars, at(3600.0));
+    assert_eq!(known.len(), 2);
+    assert!(matches!(&known[0].fact, Fact::Signal { signal } if signal.head.as_ref() == "arrived"));
+    assert!(matches!(known[1].fact, Fact::Position { actor, .. } if actor == earth));
+
+    let (_, seen_at) = workspace.last_seen(mars, earth).unwrap();
+    assert_eq!(seen_at, at(3600.0));
+
+    // Senders know what they said
+    assert_eq!(workspace.knowledge_of(earth).len(), 1);
+}
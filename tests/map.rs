@@ -0,0 +1,50 @@
+use histrion::Workspace;
+use histrion::map::{self, MapSpan, MapView, Plane};
+use histrion::export::Sampling;
+use histrion::saga;
+use histrion::time::{Instant, Interval};
+
+#[test]
+fn draws_actors_trails_and_rings() {
+    let script = saga::parse("
+        spawn Earth
+        spawn [Deep Probe]
+        as [Deep Probe] do
+            self.accel = (0, 0, 1)
+        done
+        wait 10s
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let mut view = MapView::at(Instant::default() + Interval::from_f64(10.0));
+    view.plane = Plane::XZ;
+    view.rings_around = workspace.lookup("Earth");
+
+    let mut svg = Vec::new();
+    map::write_svg(&workspace, &view, &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains(">Earth</text>"));
+    assert!(svg.contains(">[Deep Probe]</text>"));
+    assert!(svg.contains(r#"class="ring""#));
+    assert!(!svg.contains(r#"class="trail""#));
+
+    view.span = MapSpan::Trail(Sampling {
+        from: Instant::default(),
+        until: Instant::default() + Interval::from_f64(10.0),
+        step: Interval::from_f64(1.0),
+    });
+
+    let mut svg = Vec::new();
+    map::write_svg(&workspace, &view, &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+
+    assert_eq!(svg.matches(r#"class="trail""#).count(), 2);
+}
@@ -0,0 +1,130 @@
+use vek::Vec3;
+
+use histrion::{Position, Trajectory, Workspace};
+use histrion::history::EventKind;
+use histrion::proximity::first_approach;
+use histrion::saga;
+use histrion::time::{Instant, Interval};
+
+#[test]
+fn crossing_time_is_solved_not_polled() {
+    let drifting = Trajectory::Linear {
+        start_place: Position(Vec3::zero()),
+        start_time: Instant::default(),
+        start_velocity: Vec3::new(1.0, 0.0, 0.0),
+        accel: Vec3::zero(),
+    };
+
+    let buoy = Trajectory::Fixed { value: Position(Vec3::new(10.0, 3.0, 0.0)) };
+
+    let eta = first_approach(&drifting, &buoy, Instant::default(), 5.0).unwrap();
+    assert!((f64::from(eta) - 6.0).abs() < 1e-9);
+
+    assert!(first_approach(&drifting, &buoy, Instant::default(), 2.0).is_none());
+}
+
+#[test]
+fn watchers_wake_when_courses_change() {
+    let script = saga::parse("
+        spawn Ship
+        spawn Beacon
+        as Beacon do
+            self.accel = (1, 0, 0)
+            wait 10s
+            self.accel = (-1, 0, 0)
+            wait 10s
+            self.accel = (0, 0, 0)
+            wait until Ship within 19 of Beacon
+            trace 1
+        done
+        wait 25s
+        as Ship do
+            self.accel = (2, 0, 0)
+        done
+        wait 20s
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let woke = workspace.history().events().iter()
+        .find(|event| matches!(event.kind, EventKind::Traced { .. }))
+        .map(|event| f64::from(event.time))
+        .unwrap();
+
+    // The beacon rests at x = 100, and the ship covers 81 in 9 seconds
+    assert!((woke - 34.0).abs() < 1e-9);
+
+    let ship = workspace.lookup("Ship").unwrap();
+    let place = workspace.history().place_of(ship, Instant::default() + Interval::from_f64(woke));
+    assert!((place.0.x - 81.0).abs() < 1e-6);
+}
+
+#[test]
+fn later_tasks_cancel_a_stalled_watch() {
+    let script = saga::parse("
+        spawn Ship
+        spawn Beacon
+        as Beacon do
+            self.accel = (1, 0, 0)
+        done
+        wait 10s
+        as Ship do
+            wait until Beacon within 1 of Ship
+            trace 1
+        done
+        as Ship do
+            wait 10s
+            trace 2
+        done
+        as Beacon do
+            self.accel = (-5, 0, 0)
+        done
+        wait 30s
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let traces = workspace.history().events().iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::Traced { value, .. } => Some((f64::from(event.time), format!("{}", value))),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(traces, vec![(20.0, "2".to_owned())]);
+}
+
+#[test]
+fn stalled_watches_are_reported() {
+    let script = saga::parse("
+        spawn Ship
+        spawn Beacon
+        as Beacon do
+            self.accel = (1, 0, 0)
+        done
+        wait 10s
+        as Ship do
+            wait until Beacon within 1 of Ship
+        done
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    assert!(workspace.ran_dry());
+
+    let stalled = workspace.stalled_watches();
+    assert_eq!(stalled.len(), 1);
+    assert_eq!(stalled[0].actor, workspace.lookup("Ship").unwrap());
+    assert_eq!(stalled[0].subject, workspace.lookup("Beacon").unwrap());
+}
@@ -0,0 +1,100 @@
+use histrion::{Error, Workspace};
+use histrion::action::{Expr, Value};
+use histrion::history::EventKind;
+use histrion::random::Distribution;
+use histrion::saga;
+
+const DRAWS: &str = "
+    spawn Earth
+    spawn Mars
+    trace uniform(10, 20)
+    trace normal(0, 1)
+    trace choice(Earth, Mars)
+    halt
+";
+
+fn traces(seed: u64) -> (Workspace, Vec<Value>) {
+    let mut workspace = Workspace::with_seed(seed);
+    workspace.set_echo(false);
+    workspace.perform(saga::parse(DRAWS).unwrap().into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let values = workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Traced { value, .. } => Some(value.clone()),
+        _ => None,
+    }).collect();
+
+    (workspace, values)
+}
+
+#[test]
+fn draws_follow_the_seed() {
+    let (workspace, first) = traces(42);
+    let (_, again) = traces(42);
+    let (_, other) = traces(43);
+
+    assert_eq!(workspace.seed(), 42);
+    assert_eq!(first, again);
+    assert_ne!(first[.. 2], other[.. 2]);
+
+    match first[0] {
+        Value::Num(n) => assert!((10.0 .. 20.0).contains(&n.into_inner())),
+        ref other => panic!("expected a number, got {:?}", other),
+    }
+
+    let actors = [workspace.lookup("Earth").unwrap(), workspace.lookup("Mars").unwrap()];
+    match first[2] {
+        Value::ActorId(id) => assert!(actors.contains(&id)),
+        ref other => panic!("expected an actor, got {:?}", other),
+    }
+}
+
+#[test]
+fn draws_are_logged() {
+    let (workspace, values) = traces(7);
+
+    let draws = workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Drew { value, .. } => Some(value.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    assert_eq!(draws, values);
+}
+
+fn uniform(low: f64, high: f64) -> Expr {
+    let args = vec![Expr::NumConst { value: low }, Expr::NumConst { value: high }];
+    Expr::Random { dist: Distribution::Uniform, args: args.into() }
+}
+
+#[test]
+fn evaluating_a_draw_leaves_the_run_alone() {
+    let (_, expected) = traces(42);
+
+    let mut workspace = Workspace::with_seed(42);
+    workspace.set_echo(false);
+    workspace.perform(saga::parse(DRAWS).unwrap().into_inner()).unwrap();
+    workspace.evaluate(&uniform(0.0, 1.0)).unwrap();
+    workspace.simulate().unwrap();
+
+    let values = workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Traced { value, .. } => Some(value.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    let draws = workspace.history().events().iter()
+        .filter(|event| matches!(event.kind, EventKind::Drew { .. }))
+        .count();
+
+    assert_eq!(values, expected);
+    assert_eq!(draws, expected.len());
+}
+
+#[test]
+fn infinite_bounds_are_rejected() {
+    let mut workspace = Workspace::with_seed(1);
+
+    match workspace.evaluate(&uniform(f64::INFINITY, f64::NEG_INFINITY)) {
+        Err(Error::BadArgument { .. }) => {},
+        other => panic!("expected a bad argument, got {:?}", other),
+    }
+}
@@ -0,0 +1,51 @@
+use histrion::Workspace;
+use histrion::repl::{Repl, Reply};
+use histrion::saga;
+use histrion::time::Instant;
+
+fn output(reply: Reply) -> String {
+    match reply {
+        Reply::Output(text) => text,
+        other => panic!("expected output, got {:?}", other),
+    }
+}
+
+#[test]
+fn statements_share_locals_and_blocks_span_lines() {
+    let mut repl = Repl::new(Workspace::new());
+
+    output(repl.handle_line("spawn Mars"));
+    output(repl.handle_line("foo = 2"));
+
+    assert_eq!(repl.handle_line("as Mars do"), Reply::NeedMore);
+    assert_eq!(repl.handle_line("    wait 1hr"), Reply::NeedMore);
+    assert_eq!(repl.handle_line("    trace foo"), Reply::NeedMore);
+    output(repl.handle_line("done"));
+
+    assert!(output(repl.handle_line(":agenda")).contains("Mars: next task at 3600sec"));
+    assert!(output(repl.handle_line(":globals")).contains("foo = 2"));
+
+    assert!(output(repl.handle_line(":step")).contains("trace foo = 2"));
+    assert_eq!(f64::from(repl.workspace().now()), 3600.0);
+}
+
+#[test]
+fn run_until_stops_early() {
+    let mut repl = Repl::new(Workspace::new());
+
+    output(repl.handle_line("spawn Mars"));
+    output(repl.handle_line("as Mars do wait 3y trace self done"));
+
+    output(repl.handle_line(":run-until 2y"));
+    assert!(!repl.workspace().has_halted());
+    assert_eq!(repl.workspace().now(), Instant::default() + saga::parse_interval("2y").unwrap());
+
+    // Nothing is queued now, so time just passes and the session stays open
+    output(repl.handle_line(":run-until 4y"));
+    assert!(!repl.workspace().has_halted());
+    assert_eq!(repl.workspace().now(), Instant::default() + saga::parse_interval("4y").unwrap());
+    assert!(output(repl.handle_line("trace 1")).contains("trace 1"));
+
+    assert!(output(repl.handle_line(":bogus")).contains("unknown command"));
+    assert_eq!(repl.handle_line(":quit"), Reply::Quit);
+}
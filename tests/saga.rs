@@ -0,0 +1,98 @@
+use histrion::Workspace;
+use histrion::saga;
+use histrion::script::Script;
+
+const README_SAGA: &str = "
+spawn Mars
+
+foo = 2
+
+as Mars do
+    wait 1hr
+    trace foo
+    transmit #arrived(Mars)
+done
+
+listen #arrived(Mars)
+
+halt
+";
+
+#[test]
+fn run_readme_saga() {
+    let script = saga::parse(README_SAGA).unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    assert_eq!(f64::from(workspace.now()), 3600.0);
+}
+
+#[test]
+fn pretty_print_round_trips() {
+    let printed = Script::default().pretty_print();
+    let reparsed = saga::parse(&printed).unwrap();
+    assert_eq!(reparsed.pretty_print(), printed);
+}
+
+#[test]
+fn parse_errors_report_line() {
+    let err = saga::parse("spawn Mars\nas Mars do\n    wait 3 fortnights\ndone\n").unwrap_err();
+    assert_eq!(err.line, 3);
+}
+
+#[test]
+fn pretty_print_rebuilds_if_blocks() {
+    let src = "\
+spawn Mars
+
+as Mars do
+    if knows #arrived(Mars) do
+        trace 1
+        if not knows #departed(Mars) do
+            trace 2
+        else
+            trace 3
+        done
+    else
+        trace 0
+    done
+    if knows #departed(Mars) do
+    done
+    wait 60sec
+done
+
+";
+
+    let printed = saga::parse(src).unwrap().pretty_print();
+    assert_eq!(printed, src);
+
+    let reparsed = saga::parse(&printed).unwrap();
+    assert_eq!(reparsed.pretty_print(), printed);
+}
+
+#[test]
+fn comments_do_not_start_inside_strings() {
+    let script = saga::parse(r#"
+        import "//server/share/x.saga" // shared
+        wait 1hr -- "see http://example.org" // a comment
+    "#).unwrap();
+
+    assert_eq!(script.imports()[0].path.as_ref(), "//server/share/x.saga");
+    assert_eq!(format!("{}", script.into_inner()[0]), r#"wait 3600sec -- "see http://example.org""#);
+}
+
+#[test]
+fn wait_within_distances_take_length_units() {
+    let script = saga::parse("
+        wait until Ship within 1 AU of Mars
+        wait until Ship within 2 lmin of Mars
+        wait until Ship within 5 of Mars
+    ").unwrap().into_inner();
+
+    let printed: Vec<_> = script.iter().map(|action| action.to_string()).collect();
+    assert_eq!(printed[0], format!("wait until Ship within {} of Mars", 149_597_870.7 / 299_792.458));
+    assert_eq!(printed[1], "wait until Ship within 120 of Mars");
+    assert_eq!(printed[2], "wait until Ship within 5 of Mars");
+}
@@ -0,0 +1,109 @@
+use histrion::Workspace;
+use histrion::history::EventKind;
+use histrion::saga;
+
+const YEAR: f64 = 365.2425 * 24.0 * 3600.0;
+const DAY: f64 = 24.0 * 3600.0;
+
+fn trace_times(src: &str) -> Vec<(f64, String)> {
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(saga::parse(src).unwrap().into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Traced { value, .. } => Some((f64::from(event.time), value.to_string())),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn waits_can_name_a_time() {
+    let traces = trace_times("
+        wait until 10 days
+        trace 1
+        wait until year 2 day 3
+        trace 2
+        wait until 1 day
+        trace 3
+        halt
+    ");
+
+    assert_eq!(traces, vec![
+        (10.0 * DAY, "1".to_string()),
+        (YEAR + 2.0 * DAY, "2".to_string()),
+        (YEAR + 2.0 * DAY, "3".to_string()),
+    ]);
+}
+
+#[test]
+fn recurring_schedules_run_without_loops() {
+    let traces = trace_times("
+        spawn Clock
+        as Clock do
+            every 1 year from 3 until 5 do
+                trace 1
+            done
+            every 1 day until 2 days do
+                trace 2
+            done
+            wait 1s
+            trace 3
+        done
+        wait until year 10
+        halt
+    ");
+
+    let times = |label: &str| traces.iter()
+        .filter(|(_, value)| value == label)
+        .map(|&(time, _)| time)
+        .collect::<Vec<_>>();
+
+    assert_eq!(times("1"), vec![2.0 * YEAR, 3.0 * YEAR, 4.0 * YEAR]);
+    assert_eq!(times("2"), vec![0.0, DAY, 2.0 * DAY]);
+
+    // The actor's own waits aren't cancelled by its schedules
+    assert_eq!(times("3"), vec![1.0]);
+}
+
+#[test]
+fn recurrences_end_with_their_actor() {
+    let traces = trace_times("
+        spawn Clock
+        spawn Ghost
+        as Clock do
+            every 1s do
+                trace 1
+            done
+            wait 2.5s
+            die
+        done
+        as Ghost do
+            die
+            every 1s until 3s do
+                trace 2
+            done
+        done
+        wait 10s
+        halt
+    ");
+
+    assert_eq!(traces, vec![
+        (0.0, "1".to_string()),
+        (1.0, "1".to_string()),
+        (2.0, "1".to_string()),
+    ]);
+}
+
+#[test]
+fn endless_recurrences_stop_at_halt() {
+    let traces = trace_times("
+        every 1 day do
+            trace 1
+        done
+        wait 2.5 days
+        halt
+    ");
+
+    assert_eq!(traces.len(), 3);
+}
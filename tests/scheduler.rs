@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use histrion::Workspace;
+use histrion::action::*;
+use histrion::time::Interval;
+
+#[test]
+fn waking_a_listener_cancels_its_actors_next_task() {
+    let script: Arc<[Action]> = vec![
+        Action::Spawn { name: "Mars".into() },
+
+        Action::AsActor {
+            name: "Mars".into(),
+            script: vec![
+                Action::Wait { interval: Interval::from_f64(100.0) },
+            ].into(),
+        },
+
+        Action::AsActor {
+            name: "Mars".into(),
+            script: vec![
+                Action::ListenFor { head: "wake".into(), args: vec![].into() },
+            ].into(),
+        },
+
+        Action::Transmit { head: "wake".into(), args: vec![].into() },
+    ].into();
+
+    let mut workspace = Workspace::new();
+    workspace.perform(script).unwrap();
+    workspace.simulate().unwrap();
+
+    // The cancelled wait never fires, so the run halts right away
+    assert_eq!(f64::from(workspace.now()), 1.0);
+}
+
+#[test]
+fn tasks_run_in_time_order() {
+    let mut script = Vec::new();
+
+    for (i, delay) in [30.0, 10.0, 20.0].iter().enumerate() {
+        let name: Arc<str> = format!("Actor{}", i).into();
+        script.push(Action::Spawn { name: name.clone() });
+        script.push(Action::AsActor {
+            name,
+            script: vec![
+                Action::Wait { interval: Interval::from_f64(*delay) },
+            ].into(),
+        });
+    }
+
+    let mut workspace = Workspace::new();
+    workspace.perform(script.into()).unwrap();
+
+    let mut seen = Vec::new();
+    while !workspace.has_halted() {
+        workspace.update().unwrap();
+        seen.push(f64::from(workspace.now()));
+    }
+
+    assert_eq!(seen, vec![10.0, 20.0, 30.0, 31.0]);
+}
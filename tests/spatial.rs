@@ -0,0 +1,83 @@
+use specs::{Builder, World, WorldExt};
+use vek::Vec3;
+
+use histrion::Workspace;
+use histrion::action::Value;
+use histrion::history::EventKind;
+use histrion::saga;
+use histrion::spatial::SpatialIndex;
+
+#[test]
+fn scripts_find_their_neighbors() {
+    let script = saga::parse("
+        spawn Sol
+        spawn A
+        spawn B
+        spawn C
+        as A do self.accel = (1, 0, 0) done
+        as B do
+            self.accel = (3, 0, 0)
+            tag #star
+        done
+        as C do
+            self.accel = (0, -2, 0)
+            tag #star
+        done
+        wait 2s
+        as Sol do
+            trace within(self, 3)
+            trace within(self, 5)
+            trace nearest(self, 2)
+            trace nearest(self, 1, #star).first
+            trace within(self.position, 100, #star).len
+        done
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let id = |name: &str| Value::ActorId(workspace.lookup(name).unwrap());
+    let list = |names: &[&str]| Value::List(names.iter().map(|&name| id(name)).collect());
+
+    let traces = workspace.history().events().iter().filter_map(|event| match &event.kind {
+        EventKind::Traced { value, .. } => Some(value.clone()),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    assert_eq!(traces, vec![
+        list(&["A"]),
+        list(&["A", "C"]),
+        list(&["A", "C"]),
+        id("C"),
+        Value::Num(2.0.into()),
+    ]);
+}
+
+#[test]
+fn index_agrees_with_brute_force() {
+    let mut world = World::new();
+    let mut state = 12345u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 * 100.0 - 50.0
+    };
+
+    let points = (0 .. 500)
+        .map(|_| (Vec3::new(next(), next(), next()), world.create_entity().build()))
+        .collect::<Vec<_>>();
+
+    let index = SpatialIndex::new(points.clone());
+    let center = Vec3::new(3.0, -7.0, 12.0);
+
+    let mut brute = points.iter()
+        .map(|&(place, id)| (place.distance(center), id))
+        .collect::<Vec<_>>();
+    brute.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let inside = brute.iter().cloned().filter(|&(distance, _)| distance <= 20.0).collect::<Vec<_>>();
+    assert_eq!(index.within(center, 20.0), inside);
+    assert_eq!(index.nearest(center, 7, |_| true), brute[.. 7].to_vec());
+}
@@ -0,0 +1,37 @@
+use histrion::Workspace;
+use histrion::saga;
+use histrion::timeline::{self, TimelineStyle};
+
+#[test]
+fn draws_lanes_markers_and_signals() {
+    let script = saga::parse("
+        spawn Mars
+        as Mars do
+            wait 1hr
+            trace self.position
+            transmit #arrived(Mars)
+            die
+        done
+        listen #arrived(Mars)
+        wait 1hr
+        halt
+    ").unwrap();
+
+    let mut workspace = Workspace::new();
+    workspace.set_echo(false);
+    workspace.perform(script.into_inner()).unwrap();
+    workspace.simulate().unwrap();
+
+    let mut svg = Vec::new();
+    timeline::write_svg(&workspace, &TimelineStyle::default(), &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches(r#"class="lane""#).count(), 2);
+    assert!(svg.contains(">Mars</text>"));
+    assert!(svg.contains(r#"class="wait""#));
+    assert!(svg.contains(r#"class="trace""#));
+    assert!(svg.contains(r#"class="death""#));
+    assert_eq!(svg.matches(r#"class="signal""#).count(), 1);
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use histrion::{Position, Trajectory, Workspace};
+use histrion::action::*;
+use histrion::time::{Instant, Interval};
+use histrion::travel::*;
+
+fn at(secs: f64) -> Instant {
+    Instant::default() + Interval::from_f64(secs)
+}
+
+#[test]
+fn intercept_stationary_target() {
+    let profile = DriveProfile::ThrustBrake { accel: 0.01 };
+    let target = Trajectory::Fixed { value: Position((100.0, 0.0, 0.0).into()) };
+
+    let (eta, course) = plan_intercept(Position::default(), at(0.0), &target, profile).unwrap();
+
+    let expected = 2.0 * (100.0f64 / 0.01).sqrt();
+    assert!((f64::from(eta) - expected).abs() < 1e-6);
+    assert!((course.sample_at(at(expected / 2.0)).0.x - 50.0).abs() < 1e-6);
+    assert!((course.sample_at(eta).0.x - 100.0).abs() < 1e-6);
+    assert!(course.velocity_at(eta).magnitude() < 1e-6);
+}
+
+#[test]
+fn intercept_moving_target() {
+    let profile = DriveProfile::ThrustCoast { accel: 0.01, max_speed: 0.5 };
+    let target = Trajectory::Linear {
+        start_place: Position((1000.0, 0.0, 0.0).into()),
+        start_time: at(0.0),
+        start_velocity: (0.0, 0.1, 0.0).into(),
+        accel: (0.0, 0.0, 0.0).into(),
+    };
+
+    let (eta, course) = plan_intercept(Position::default(), at(0.0), &target, profile).unwrap();
+
+    let gap = course.sample_at(eta).0 - target.sample_at(eta).0;
+    assert!(gap.magnitude() < 1e-3);
+}
+
+#[test]
+fn outrun_target_is_unreachable() {
+    let profile = DriveProfile::ThrustCoast { accel: 0.01, max_speed: 0.1 };
+    let target = Trajectory::Linear {
+        start_place: Position((1000.0, 0.0, 0.0).into()),
+        start_time: at(0.0),
+        start_velocity: (0.5, 0.0, 0.0).into(),
+        accel: (0.0, 0.0, 0.0).into(),
+    };
+
+    assert!(plan_intercept(Position::default(), at(0.0), &target, profile).is_none());
+}
+
+#[test]
+fn travel_suspends_until_arrival() {
+    let script: Arc<[Action]> = vec![
+        Action::Spawn { name: "Proxima".into() },
+        Action::Spawn { name: "Fleet".into() },
+
+        Action::AsActor {
+            name: "Proxima".into(),
+            script: vec![
+                Action::SetAccel { value: (1e-3, 0.0, 0.0).into() },
+            ].into(),
+        },
+
+        Action::AsActor {
+            name: "Fleet".into(),
+            script: vec![
+                Action::Wait { interval: Interval::from_f64(10.0) },
+                Action::TravelTo {
+                    target: Expr::Var { name: "Proxima".into() }.into(),
+                    profile: DriveProfile::ThrustBrake { accel: 1e-2 },
+                },
+                Action::Transmit {
+                    head: "arrived".into(),
+                    args: vec![].into(),
+                },
+            ].into(),
+        },
+
+        Action::ListenFor { head: "arrived".into(), args: vec![].into() },
+        Action::Halt,
+    ].into();
+
+    let mut workspace = Workspace::new();
+    workspace.perform(script).unwrap();
+    workspace.simulate().unwrap();
+
+    // Proxima is at x = t²/2000 when the fleet arrives, so a thrust-brake trip
+    // of 20√d seconds from t = 10 gives d = (10 + 20√d)²/2000, or √d = φ/4
+    let golden = (1.0 + 5f64.sqrt()) / 2.0;
+    let arrival = 10.0 + 5.0 * golden;
+    assert!((f64::from(workspace.now()) - arrival).abs() < 1e-6, "{}", f64::from(workspace.now()));
+}